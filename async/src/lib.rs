@@ -2,47 +2,179 @@
 #![deny(bad_style, missing_docs)]
 #![doc = include_str!("../README.md")]
 
-use hidg_core::{check_read, check_write, AsDevicePath};
+use hidg_core::{check_error, check_read, check_write, check_write_error, AsDevicePath};
 
-pub use hidg_core::{Class, Error, Result, StateChange, ValueChange};
+pub use hidg_core::{
+    Boot, Class, Coalesce, ConnectionState, ConnectionWatch, ConnectionWatcher, Demux, EmptyReport,
+    Error, IdReport, Identified, Pacer, Protocol, RawReport, Result, StateChange, ValueChange,
+};
 
 #[cfg(feature = "keyboard")]
 pub use hidg_core::{
-    Key, KeyStateChanges, Keyboard, KeyboardInput, KeyboardOutput, Led, LedStateChanges, Leds,
-    Modifiers,
+    ExtendedKeyboard, ExtendedKeyboardOutput, Key, KeyStateChanges, Keyboard, KeyboardInput,
+    KeyboardOutput, Led, LedStateChanges, Leds, Modifiers,
 };
 
 #[cfg(feature = "mouse")]
 pub use hidg_core::{
-    Button, Buttons, Mouse, MouseInput, MouseInputChange, MouseInputChanges, MouseOutput,
+    Axis, BootMouseInput, Button, Buttons, GenericMouseInput, GenericMouseInputChange,
+    GenericMouseInputChanges, HiResMouse, HiResMouseInput, Mouse, MouseInput, MouseInputChange,
+    MouseInputChanges, MouseMotion, MouseOutput, PanMouse, PanMouseInput, ResolutionMultiplier,
+    Scroller, WheelValue,
 };
 
-use core::marker::PhantomData;
+#[cfg(feature = "power")]
+pub use hidg_core::{PowerDevice, PowerFlags, PowerStatus};
+
+#[cfg(feature = "raw-hid")]
+pub use hidg_core::RawHid;
+
+#[cfg(feature = "telephony")]
+pub use hidg_core::{
+    LitPhoneLeds, PhoneButton, PhoneButtonChanges, PhoneButtons, PhoneLed, PhoneLedChanges,
+    PhoneLeds, PressedPhoneButtons, Telephony, TelephonyInput, TelephonyOutput,
+};
+
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+pub use hidg_core::{CompositeEvent, CompositeInput, CompositeTarget};
+
+#[cfg(feature = "ctaphid")]
+pub use hidg_core::ctaphid;
+
+#[cfg(feature = "evdev")]
+pub use hidg_core::evdev;
+
+#[cfg(all(feature = "stream", feature = "keyboard"))]
+pub use hidg_core::stream::LedChanges;
+
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+mod composite;
+
+mod pace;
+#[cfg(feature = "raw-hid")]
+mod raw_hid;
+mod reconnect;
+mod split;
+
+#[cfg(feature = "mock")]
+mod mock;
+
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+pub use composite::Composite;
+
+pub use pace::Paced;
+pub use reconnect::Reconnecting;
+pub use split::{InputHalf, OutputHalf, ReuniteError};
+
+#[cfg(feature = "mock")]
+pub use mock::{Fault, MockTransport};
+
+use core::{
+    future::poll_fn,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
 use std::{
     fs::{File, OpenOptions},
-    io::{Read, Write},
+    path::PathBuf,
 };
 
 use async_io::Async;
 use blocking::unblock as asyncify;
+use futures_io::{AsyncRead, AsyncWrite};
+
+/// Report transport
+///
+/// Each call transfers exactly one report like reads and writes of `/dev/hidgX` do.
+/// Implemented for any [`AsyncRead`] + [`AsyncWrite`] stream.
+pub trait Transport {
+    /// Poll for writing input report
+    fn poll_write_report(
+        &mut self,
+        cx: &mut Context<'_>,
+        report: &[u8],
+    ) -> Poll<std::io::Result<usize>>;
+
+    /// Poll for reading output report
+    fn poll_read_report(
+        &mut self,
+        cx: &mut Context<'_>,
+        report: &mut [u8],
+    ) -> Poll<std::io::Result<usize>>;
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> Transport for T {
+    fn poll_write_report(
+        &mut self,
+        cx: &mut Context<'_>,
+        report: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(self).poll_write(cx, report)
+    }
+
+    fn poll_read_report(
+        &mut self,
+        cx: &mut Context<'_>,
+        report: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(self).poll_read(cx, report)
+    }
+}
 
 /// HID Gadget Device
-pub struct Device<C: Class> {
-    file: Async<File>,
+pub struct Device<C: Class, T = Async<File>> {
+    transport: T,
+    protocol: Protocol,
     _class: PhantomData<C>,
 }
 
 impl<C: Class> Device<C> {
     /// Open device by path or name or number
     pub async fn open(device: impl AsDevicePath) -> Result<Self> {
-        let path = device.as_device_path();
-        let file = Async::new(
-            asyncify(move || OpenOptions::new().read(true).write(true).open(path)).await?,
-        )?;
-        Ok(Self {
-            file,
+        let file = open_file(device.as_device_path()).await?;
+        Ok(Self::new(file))
+    }
+}
+
+/// Open device file for reading and writing
+async fn open_file(path: PathBuf) -> Result<Async<File>> {
+    let open = {
+        let path = path.clone();
+        move || OpenOptions::new().read(true).write(true).open(path)
+    };
+    asyncify(open)
+        .await
+        .and_then(Async::new)
+        .map_err(|error| Error::open(path, error))
+}
+
+impl<C: Class, T: Transport> Device<C, T> {
+    /// Create device using specific transport
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            protocol: Protocol::default(),
             _class: PhantomData,
-        })
+        }
+    }
+
+    /// Get reference to underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get protocol of input reports
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Set protocol of input reports
+    ///
+    /// Gadget driver handles SET_PROTOCOL request itself and does not expose selected protocol
+    /// to userspace, so it should be configured explicitly. Default is report protocol.
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
     }
 
     /// Send input report
@@ -50,9 +182,29 @@ impl<C: Class> Device<C> {
     where
         C::Input: AsRef<[u8]>,
     {
-        self.file.writable().await?;
-        let raw = input.as_ref();
-        let len = self.file.get_ref().write(raw)?;
+        self.send_report(input).await
+    }
+
+    /// Send input report encoded for current protocol
+    pub async fn protocol_input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C: Boot,
+        C::Input: AsRef<[u8]>,
+    {
+        match self.protocol {
+            Protocol::Boot => self.send_report(&C::boot_input(input)).await,
+            Protocol::Report => self.send_report(input).await,
+        }
+    }
+
+    /// Send input report of any type
+    ///
+    /// Useful when interface carries several report types distinguished by report ID.
+    pub async fn send_report(&mut self, report: &impl AsRef<[u8]>) -> Result<()> {
+        let raw = report.as_ref();
+        let len = poll_fn(|cx| self.transport.poll_write_report(cx, raw))
+            .await
+            .map_err(check_write_error)?;
 
         check_write(len, raw.len())
     }
@@ -62,12 +214,107 @@ impl<C: Class> Device<C> {
     where
         C::Output: AsMut<[u8]>,
     {
-        self.file.readable().await?;
         let raw = output.as_mut();
-        let len = self.file.get_ref().read(raw)?;
+        let len = poll_fn(|cx| self.transport.poll_read_report(cx, raw))
+            .await
+            .map_err(check_error)?;
+
+        check_read(len, raw.len())?;
+
+        Ok(())
+    }
+
+    /// Receive output report and dispatch it by report ID
+    ///
+    /// Returns ID of received report.
+    pub async fn receive_report(&mut self, outputs: &mut impl Demux) -> Result<u8> {
+        let mut raw = vec![0; outputs.max_len()];
+        let len = poll_fn(|cx| self.transport.poll_read_report(cx, &mut raw))
+            .await
+            .map_err(check_error)?;
+
+        outputs.demux(&raw[..len])
+    }
+
+    /// Receive feature report set by host
+    ///
+    /// Gadget driver passes data of SET_REPORT requests to reads when function has no OUT
+    /// endpoint, so feature reports share the channel with output reports.
+    pub async fn feature(&mut self, feature: &mut C::Feature) -> Result<()>
+    where
+        C::Feature: AsMut<[u8]>,
+    {
+        let raw = feature.as_mut();
+        let len = poll_fn(|cx| self.transport.poll_read_report(cx, raw))
+            .await
+            .map_err(check_error)?;
 
         check_read(len, raw.len())?;
 
         Ok(())
     }
 }
+
+/// Create device on mock transport and get transport handle to inspect transferred reports
+#[cfg(all(
+    test,
+    feature = "mock",
+    any(feature = "keyboard", feature = "mouse", feature = "raw-hid")
+))]
+fn mocked<C: Class>() -> (Device<C, MockTransport>, MockTransport) {
+    let mock = MockTransport::new();
+    (Device::new(mock.clone()), mock)
+}
+
+#[cfg(all(test, feature = "mock", feature = "keyboard"))]
+mod test {
+    use super::*;
+
+    #[smol_potat::test]
+    async fn mock_device() {
+        let (mut device, mock) = crate::mocked::<Keyboard>();
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        device.input(&input).await.unwrap();
+        assert_eq!(mock.inputs(), [input.as_ref().to_vec()]);
+
+        mock.fail_write(Fault::Shutdown);
+        assert!(device.input(&input).await.is_err());
+
+        let reader = smol::spawn(async move {
+            let mut output = Keyboard.output();
+            device.output(&mut output).await.unwrap();
+            output
+        });
+        mock.push_output([0x02]);
+
+        let output = reader.await;
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::CapsLock]);
+    }
+
+    #[cfg(feature = "mouse")]
+    #[smol_potat::test]
+    async fn boot_protocol_device() {
+        let (mut device, mock) = crate::mocked::<Mouse>();
+
+        let mut input = Mouse.input();
+        input.press_button(Button::Primary);
+        input.set_pointer((-200, 3));
+        input.set_wheel(1);
+        device.protocol_input(&input).await.unwrap();
+        device.set_protocol(Protocol::Boot);
+        device.protocol_input(&input).await.unwrap();
+        assert_eq!(
+            mock.take_inputs(),
+            [vec![1, 0x38, 0xff, 3, 0, 1], vec![1, 0x80, 3]]
+        );
+
+        let (mut input_half, output_half) = device.split();
+        assert_eq!(input_half.protocol(), Protocol::Boot);
+        input_half.protocol_input(&input).await.unwrap();
+        assert_eq!(mock.take_inputs(), [[1, 0x80, 3]]);
+        let device = input_half.reunite(output_half).unwrap();
+        assert_eq!(device.protocol(), Protocol::Boot);
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use async_io::{Async, Timer};
+
+use crate::{Class, Coalesce, Device, Pacer, Result, Transport};
+
+/// Device which sends input reports not faster than once per interval
+///
+/// Relative pointer and wheel deltas pushed while waiting is coalesced into one report,
+/// but button and key transitions is always sent separately.
+pub struct Paced<C: Class, T = Async<std::fs::File>> {
+    device: Device<C, T>,
+    pacer: Pacer<C::Input>,
+}
+
+impl<C: Class, T: Transport> Paced<C, T>
+where
+    C::Input: AsRef<[u8]> + Coalesce + Clone,
+{
+    /// Create paced device with given interval
+    ///
+    /// Usually interval should match polling interval of endpoint (`bInterval`).
+    pub fn new(device: Device<C, T>, interval: Duration) -> Self {
+        Self {
+            device,
+            pacer: Pacer::new(interval),
+        }
+    }
+
+    /// Get reference to underlying device
+    pub fn device(&self) -> &Device<C, T> {
+        &self.device
+    }
+
+    /// Get underlying device
+    ///
+    /// Pending reports is discarded.
+    pub fn into_inner(self) -> Device<C, T> {
+        self.device
+    }
+
+    /// Get reference to queue of pending reports
+    pub fn pacer(&self) -> &Pacer<C::Input> {
+        &self.pacer
+    }
+
+    /// Get mutable reference to queue of pending reports
+    ///
+    /// Reports pushed directly is sent by next [`Paced::poll`].
+    pub fn pacer_mut(&mut self) -> &mut Pacer<C::Input> {
+        &mut self.pacer
+    }
+
+    /// Queue input report
+    ///
+    /// Report is sent immediately when interval since previous report is elapsed.
+    pub async fn input(&mut self, input: &C::Input) -> Result<()> {
+        self.pacer.push(input);
+        self.poll().await
+    }
+
+    /// Send pending report if it is due
+    ///
+    /// Call it when [`Pacer::deadline`] is reached to not delay pending reports.
+    pub async fn poll(&mut self) -> Result<()> {
+        let now = Instant::now();
+        if let Some(input) = self.pacer.peek(now) {
+            self.device.input(input).await?;
+            self.pacer.pop(now);
+        }
+        Ok(())
+    }
+
+    /// Send all pending reports
+    ///
+    /// Resolves when last report is sent.
+    pub async fn flush(&mut self) -> Result<()> {
+        while let Some(deadline) = self.pacer.deadline() {
+            Timer::at(deadline).await;
+            self.poll().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mock", feature = "mouse"))]
+mod test {
+    use super::*;
+    use crate::{Button, Mouse};
+
+    #[smol_potat::test]
+    async fn paced_device() {
+        let (device, mock) = crate::mocked::<Mouse>();
+        let interval = std::time::Duration::from_millis(100);
+        let mut device = Paced::new(device, interval);
+
+        let mut motion = Mouse.input();
+        motion.set_pointer((2, 1));
+        for _ in 0..5 {
+            device.input(&motion).await.unwrap();
+        }
+        let mut click = Mouse.input();
+        click.press_button(Button::Primary);
+        device.input(&click).await.unwrap();
+        device.input(&click).await.unwrap();
+        assert_eq!(mock.inputs().len(), 1);
+        assert_eq!(device.pacer().len(), 2);
+
+        device.flush().await.unwrap();
+        assert_eq!(device.pacer().len(), 0);
+        let inputs = mock.take_inputs();
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(inputs[1], [0, 8, 0, 4, 0, 0]);
+        assert_eq!(inputs[2], [1, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,134 @@
+use core::{future::Future, pin::Pin};
+use std::fs::File;
+
+use async_io::Async;
+use hidg_core::{AsDevicePath, Reconnect};
+
+use crate::{open_file, Class, ConnectionState, ConnectionWatcher, Device, Result, Transport};
+
+type Opener<T> = Box<dyn FnMut() -> Pin<Box<dyn Future<Output = Result<T>> + Send>> + Send>;
+
+/// Device which survives host disconnects
+///
+/// Keeps current input report and re-sends it when connection is restored.
+/// Device file is re-opened when it becomes unusable, for example after gadget re-enumeration.
+pub struct Reconnecting<C: Class, T = Async<File>> {
+    open: Opener<T>,
+    inner: Reconnect<Device<C, T>, C::Input>,
+}
+
+impl<C: Class> Reconnecting<C>
+where
+    C::Input: Default,
+{
+    /// Create reconnecting device by path or name or number
+    ///
+    /// Device file is opened lazily on first transfer.
+    pub fn open(device: impl AsDevicePath) -> Self {
+        let path = device.as_device_path();
+        Self::with_opener(move || open_file(path.clone()))
+    }
+}
+
+impl<C: Class, T: Transport> Reconnecting<C, T> {
+    /// Create reconnecting device using function which opens transport
+    pub fn with_opener<F>(mut open: impl FnMut() -> F + Send + 'static) -> Self
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+        C::Input: Default,
+    {
+        Self {
+            open: Box::new(move || Box::pin(open())),
+            inner: Reconnect::new(Default::default()),
+        }
+    }
+
+    /// Get current connection state
+    pub fn state(&self) -> ConnectionState {
+        self.inner.state()
+    }
+
+    /// Subscribe to connection state changes
+    pub fn subscribe(&self) -> ConnectionWatcher {
+        self.inner.subscribe()
+    }
+
+    /// Get current input report
+    pub fn current(&self) -> &C::Input {
+        self.inner.current()
+    }
+
+    /// Send input report
+    ///
+    /// Report is kept as current state even if sending fails.
+    pub async fn input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C::Input: AsRef<[u8]> + Clone,
+    {
+        self.inner.set_current(input.clone());
+        self.reconnect().await
+    }
+
+    /// Re-send current input report
+    ///
+    /// Call it periodically while disconnected to restore connection.
+    pub async fn reconnect(&mut self) -> Result<()>
+    where
+        C::Input: AsRef<[u8]>,
+    {
+        self.ensure_open().await?;
+        let (device, input) = self.inner.parts_mut().unwrap();
+        let result = device.input(input).await;
+        self.inner.update(result)
+    }
+
+    /// Receive output report
+    pub async fn output(&mut self, output: &mut C::Output) -> Result<()>
+    where
+        C::Output: AsMut<[u8]>,
+    {
+        self.ensure_open().await?;
+        let (device, _) = self.inner.parts_mut().unwrap();
+        let result = device.output(output).await;
+        self.inner.update(result)
+    }
+
+    async fn ensure_open(&mut self) -> Result<()> {
+        if self.inner.is_open() {
+            return Ok(());
+        }
+        let result = (self.open)().await.map(Device::new);
+        self.inner.opened(result)
+    }
+}
+
+#[cfg(all(test, feature = "mock", feature = "keyboard"))]
+mod test {
+    use super::*;
+    use crate::{Error, Fault, Key, Keyboard, MockTransport};
+
+    #[smol_potat::test]
+    async fn reconnecting_device() {
+        let mock = MockTransport::new();
+        let mut device = Reconnecting::<Keyboard, _>::with_opener({
+            let mock = mock.clone();
+            move || core::future::ready(Ok(mock.clone()))
+        });
+        let mut watcher = device.subscribe();
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        device.input(&input).await.unwrap();
+        assert_eq!(watcher.changed().await, ConnectionState::Connected);
+
+        mock.fail_write(Fault::Shutdown);
+        let error = device.input(&input).await.unwrap_err();
+        assert!(matches!(error, Error::NotConnected));
+        assert_eq!(watcher.changed().await, ConnectionState::Disconnected);
+
+        mock.take_inputs();
+        device.reconnect().await.unwrap();
+        assert_eq!(watcher.changed().await, ConnectionState::Connected);
+        assert_eq!(mock.take_inputs(), [input.as_ref().to_vec()]);
+    }
+}
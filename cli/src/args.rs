@@ -34,4 +34,43 @@ pub enum Cmd {
         #[arg(value_parser, default_value = "hidg0")]
         path: std::path::PathBuf,
     },
+
+    /// Watch keyboard LED state changes
+    Monitor {
+        /// Print events as JSON lines
+        #[arg(short, long)]
+        json: bool,
+
+        #[arg(value_parser, default_value = "hidg0")]
+        path: std::path::PathBuf,
+    },
+
+    /// Forward local input event devices to gadget
+    Forward {
+        /// Keyboard gadget device
+        #[arg(short, long)]
+        keyboard: Option<std::path::PathBuf>,
+
+        /// Mouse gadget device
+        #[arg(short, long)]
+        mouse: Option<std::path::PathBuf>,
+
+        /// Mouse gadget has horizontal wheel
+        #[arg(long)]
+        pan: bool,
+
+        /// Do not grab input event devices exclusively
+        #[arg(long)]
+        no_grab: bool,
+
+        /// Input event devices like /dev/input/event0
+        #[arg(value_parser, required = true)]
+        inputs: Vec<std::path::PathBuf>,
+    },
+
+    /// Generate shell completions
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
 }
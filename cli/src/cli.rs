@@ -7,9 +7,11 @@ use rustyline::{
     validate::Validator,
     Context, Helper, Result,
 };
+use std::borrow::Cow;
 
 pub struct Cli {
     class: Class,
+    cmds: Vec<Entry>,
     keys: Vec<Entry>,
 }
 
@@ -17,6 +19,21 @@ impl Helper for Cli {}
 
 impl Cli {
     pub fn new(class: Class) -> Self {
+        let cmds = match class {
+            Class::Keyboard => vec![
+                Entry::Cmd(Command::State),
+                Entry::Cmd(Command::Press),
+                Entry::Cmd(Command::Release),
+            ],
+            Class::Mouse => vec![
+                Entry::Cmd(Command::State),
+                Entry::Cmd(Command::Press),
+                Entry::Cmd(Command::Release),
+                Entry::Cmd(Command::Move),
+                Entry::Cmd(Command::Wheel),
+            ],
+        };
+
         let keys = match class {
             Class::Keyboard => hidg::Key::VARIANTS
                 .iter()
@@ -30,10 +47,31 @@ impl Cli {
                 .collect(),
         };
 
-        Self { class, keys }
+        Self { class, cmds, keys }
+    }
+
+    fn command(&self, name: &str) -> Option<Command> {
+        self.cmds.iter().find_map(|entry| match entry {
+            Entry::Cmd(cmd) if cmd.as_ref() == name => Some(*cmd),
+            _ => None,
+        })
+    }
+
+    fn is_key(&self, name: &str) -> bool {
+        match self.class {
+            Class::Keyboard => name.parse::<Key>().is_ok(),
+            Class::Mouse => name.parse::<Button>().is_ok(),
+        }
     }
 }
 
+/// Find entries which names starts with prefix
+fn matches<'a>(entries: &'a [Entry], prefix: &'a str) -> impl Iterator<Item = &'a Entry> + 'a {
+    entries
+        .iter()
+        .filter(move |entry| entry.as_ref().starts_with(prefix))
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum Command {
     State,
@@ -43,6 +81,25 @@ pub enum Command {
     Wheel,
 }
 
+impl Command {
+    /// Expected arguments
+    pub fn args(&self, class: Class) -> Option<&'static str> {
+        use Command::*;
+        match (self, class) {
+            (State, _) => None,
+            (Press | Release, Class::Keyboard) => Some("<key>..."),
+            (Press | Release, Class::Mouse) => Some("<button>..."),
+            (Move, _) => Some("<x> <y>"),
+            (Wheel, _) => Some("<delta>"),
+        }
+    }
+
+    /// Command takes key or button names
+    pub fn takes_keys(&self) -> bool {
+        matches!(self, Command::Press | Command::Release)
+    }
+}
+
 impl AsRef<str> for Command {
     fn as_ref(&self) -> &str {
         use Command::*;
@@ -74,27 +131,114 @@ impl AsRef<str> for Entry {
     }
 }
 
+/// Inline hint
+#[derive(Clone, Debug)]
+pub enum Suggestion {
+    /// Rest of command or key name
+    Rest(String),
+    /// Expected arguments
+    Args(String),
+}
+
+impl Hint for Suggestion {
+    fn display(&self) -> &str {
+        match self {
+            Suggestion::Rest(rest) => rest,
+            Suggestion::Args(args) => args,
+        }
+    }
+    fn completion(&self) -> Option<&str> {
+        match self {
+            Suggestion::Rest(rest) => Some(rest),
+            Suggestion::Args(_) => None,
+        }
+    }
+}
+
 impl Hinter for Cli {
-    type Hint = Entry;
+    type Hint = Suggestion;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<Self::Hint> {
+        if pos < line.len() || line.is_empty() {
+            return None;
+        }
+
+        let Some((cmd, args)) = line.split_once(' ') else {
+            if let Some(cmd) = self.command(line) {
+                return cmd
+                    .args(self.class)
+                    .map(|args| Suggestion::Args(format!(" {args}")));
+            }
+            return matches(&self.cmds, line)
+                .next()
+                .map(|entry| Suggestion::Rest(entry.as_ref()[line.len()..].into()));
+        };
+
+        let cmd = self.command(cmd)?;
+        let word = args.rsplit(' ').next().unwrap_or_default();
 
-    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<Self::Hint> {
-        None
+        if args.trim().is_empty() {
+            cmd.args(self.class)
+                .map(|args| Suggestion::Args(args.into()))
+        } else if cmd.takes_keys() && !word.is_empty() {
+            matches(&self.keys, word)
+                .find(|entry| entry.as_ref().len() > word.len())
+                .map(|entry| Suggestion::Rest(entry.as_ref()[word.len()..].into()))
+        } else {
+            None
+        }
     }
 }
 
-impl Highlighter for Cli {}
+impl Highlighter for Cli {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let Some((cmd, args)) = line.split_once(' ') else {
+            return Cow::Borrowed(line);
+        };
 
-impl Validator for Cli {}
+        if !self.command(cmd).is_some_and(|cmd| cmd.takes_keys()) {
+            return Cow::Borrowed(line);
+        }
 
-impl Hint for Entry {
-    fn display(&self) -> &str {
-        self.as_ref()
+        let mut words = args.split(' ').peekable();
+        let mut highlighted = format!("{cmd} ");
+        let mut unknown = false;
+
+        while let Some(word) = words.next() {
+            let last = words.peek().is_none();
+            // the word being typed is not an error while it is a prefix of some name
+            if word.is_empty()
+                || self.is_key(word)
+                || (last && matches(&self.keys, word).next().is_some())
+            {
+                highlighted.push_str(word);
+            } else {
+                highlighted.push_str(&format!("\x1b[31m{word}\x1b[0m"));
+                unknown = true;
+            }
+            if !last {
+                highlighted.push(' ');
+            }
+        }
+
+        if unknown {
+            Cow::Owned(highlighted)
+        } else {
+            Cow::Borrowed(line)
+        }
     }
-    fn completion(&self) -> Option<&str> {
-        Some(self.as_ref())
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(format!("\x1b[2m{hint}\x1b[0m"))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        true
     }
 }
 
+impl Validator for Cli {}
+
 impl Candidate for Entry {
     fn display(&self) -> &str {
         self.as_ref()
@@ -115,35 +259,17 @@ impl Completer for Cli {
     ) -> Result<(usize, Vec<Self::Candidate>)> {
         let line = line.split_at(pos).0;
         if let Some((cmd, args)) = line.split_once(' ') {
-            let pos = cmd.len()
-                + 1
-                + if let Some(pos) = args.rfind(' ') {
-                    pos
-                } else {
-                    0
-                };
-            match cmd {
-                "press" | "release" => Ok((pos, self.keys.clone())),
+            let pos = args.rfind(' ').map(|pos| pos + 1).unwrap_or_default();
+            let word = &args[pos..];
+            match self.command(cmd) {
+                Some(cmd) if cmd.takes_keys() => Ok((
+                    cmd.as_ref().len() + 1 + pos,
+                    matches(&self.keys, word).copied().collect(),
+                )),
                 _ => Ok((0, vec![])),
             }
         } else {
-            Ok((
-                0,
-                match self.class {
-                    Class::Keyboard => vec![
-                        Entry::Cmd(Command::State),
-                        Entry::Cmd(Command::Press),
-                        Entry::Cmd(Command::Release),
-                    ],
-                    Class::Mouse => vec![
-                        Entry::Cmd(Command::State),
-                        Entry::Cmd(Command::Press),
-                        Entry::Cmd(Command::Release),
-                        Entry::Cmd(Command::Move),
-                        Entry::Cmd(Command::Wheel),
-                    ],
-                },
-            ))
+            Ok((0, matches(&self.cmds, line).copied().collect()))
         }
     }
 }
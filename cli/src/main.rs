@@ -1,85 +1,226 @@
 mod args;
 mod cli;
+mod monitor;
 
 use args::{Args, Class, Cmd};
 use cli::Cli;
-use hidg::{Button, Device, Key, Keyboard, Mouse, StateChange, ValueChange};
+use hidg::{
+    evdev::{EventDevice, Forward, ForwardMouse},
+    Button, Class as _, Device, Key, Keyboard, Mouse, PanMouse, StateChange,
+};
 use rustyline::{error::ReadlineError, Editor};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 fn main() -> anyhow::Result<()> {
     let args: Args = clap::Parser::parse();
 
     match args.cmd {
-        Cmd::Repl { class, path } => match class {
-            Class::Keyboard => {
-                let mut dev = Device::<Keyboard>::open(path)?;
-
-                let mut rl = Editor::<Cli>::new()?;
-                rl.set_helper(Cli::new(class).into());
-                loop {
-                    let readline = rl.readline(">> ");
-                    match readline {
-                        Ok(line) => {
-                            rl.add_history_entry(line.as_str());
-
-                            let mut words = line.split(char::is_whitespace);
-                            if let Some(cmd) = words.next() {
-                                match cmd {
-                                    "" => {
-                                        let keys = dev
-                                            .input()
-                                            .pressed()
-                                            .map(|k| k.to_string())
-                                            .collect::<Vec<_>>()
-                                            .join(" ");
-
-                                        let leds = dev
-                                            .output()
-                                            .lit()
-                                            .map(|l| l.to_string())
-                                            .collect::<Vec<_>>()
-                                            .join(" ");
-
-                                        println!("Keys pressed: {}, Leds lit: {}", keys, leds);
-                                    }
-                                    "press" => {
-                                        let keys = words
-                                            .map(|k| k.parse().map(StateChange::press))
-                                            .collect::<Result<Vec<StateChange<Key>>, _>>()?;
-                                        dev.updates(keys)?;
-                                    }
-                                    "release" => {
-                                        let keys = words
-                                            .map(|k| k.parse().map(StateChange::release))
-                                            .collect::<Result<Vec<StateChange<Key>>, _>>()?;
-                                        dev.updates(keys)?;
-                                    }
-                                    other => {
-                                        println!("Unknown command: {}", other);
-                                    }
-                                }
-                            }
-                        }
-                        Err(ReadlineError::Interrupted) => {
-                            println!("CTRL-C");
-                            break;
-                        }
-                        Err(ReadlineError::Eof) => {
-                            println!("CTRL-D");
-                            break;
-                        }
-                        Err(err) => {
-                            println!("Error: {:?}", err);
-                            break;
-                        }
-                    }
-                }
+        Cmd::Repl { class, path } => repl(class, path),
+        Cmd::Monitor { json, path } => monitor::monitor(path, json),
+        Cmd::Forward {
+            keyboard,
+            mouse,
+            pan: false,
+            no_grab,
+            inputs,
+        } => forward::<Mouse>(keyboard, mouse, !no_grab, inputs),
+        Cmd::Forward {
+            keyboard,
+            mouse,
+            pan: true,
+            no_grab,
+            inputs,
+        } => forward::<PanMouse>(keyboard, mouse, !no_grab, inputs),
+        Cmd::Completions { shell } => {
+            let mut cmd = <Args as clap::CommandFactory>::command();
+            clap_complete::generate(shell, &mut cmd, "hidg", &mut std::io::stdout());
+            Ok(())
+        }
+    }
+}
+
+fn forward<M: ForwardMouse>(
+    keyboard: Option<PathBuf>,
+    mouse: Option<PathBuf>,
+    grab: bool,
+    inputs: Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    if keyboard.is_none() && mouse.is_none() {
+        anyhow::bail!("At least one of keyboard or mouse gadget devices should be specified");
+    }
+
+    let keyboard = keyboard.map(Device::<Keyboard>::open).transpose()?;
+    let mouse = mouse.map(Device::<M>::open).transpose()?;
+
+    let mut sources = inputs
+        .into_iter()
+        .map(|path| {
+            let source = EventDevice::open(path)?;
+            if grab {
+                source.grab(true)?;
+            }
+            Ok(source)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Forward::<M>::new(keyboard, mouse).run(&mut sources)?;
+
+    Ok(())
+}
+
+/// Get path to REPL history file
+fn history_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(dir.join("hidg").join("history"))
+}
+
+fn repl(class: Class, path: PathBuf) -> anyhow::Result<()> {
+    let mut rl = Editor::<Cli>::new()?;
+    rl.set_helper(Cli::new(class).into());
+
+    let history = history_path();
+    if let Some(history) = &history {
+        // history file is missing on first run
+        let _ = rl.load_history(history);
+    }
+
+    let result = match class {
+        Class::Keyboard => repl_keyboard(&mut rl, path),
+        Class::Mouse => repl_mouse(&mut rl, path),
+    };
+
+    if let Some(history) = &history {
+        if let Some(dir) = history.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        rl.save_history(history)?;
+    }
+
+    result
+}
+
+fn repl_keyboard(rl: &mut Editor<Cli>, path: PathBuf) -> anyhow::Result<()> {
+    let mut dev = Device::<Keyboard>::open(path)?;
+    let mut input = Keyboard.input();
+    let output = Arc::new(Mutex::new(Keyboard.output()));
+
+    {
+        let mut dev = dev.try_clone()?;
+        let output = output.clone();
+        std::thread::spawn(move || {
+            let mut report = Keyboard.output();
+            while dev.output(&mut report).is_ok() {
+                *output.lock().unwrap() = report;
+            }
+        });
+    }
+
+    while let Some(line) = readline(rl)? {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None | Some("state") => {
+                let keys = input
+                    .pressed()
+                    .map(|k| k.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let leds = output
+                    .lock()
+                    .unwrap()
+                    .lit()
+                    .map(|l| l.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                println!("Keys pressed: {}, Leds lit: {}", keys, leds);
+            }
+            Some(cmd @ ("press" | "release")) => {
+                let keys = words
+                    .map(|k| k.parse().map(|k| StateChange::new(k, cmd == "press")))
+                    .collect::<Result<Vec<StateChange<Key>>, _>>()?;
+                input.extend(keys);
+                dev.input(&input)?;
+            }
+            Some(other) => {
+                println!("Unknown command: {}", other);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn repl_mouse(rl: &mut Editor<Cli>, path: PathBuf) -> anyhow::Result<()> {
+    let mut dev = Device::<Mouse>::open(path)?;
+    let mut input = Mouse.input();
+
+    while let Some(line) = readline(rl)? {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None | Some("state") => {
+                let buttons = input
+                    .pressed()
+                    .map(|b| b.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                println!(
+                    "Buttons pressed: {}, Pointer: {:?}, Wheel: {}",
+                    buttons,
+                    input.pointer(),
+                    input.wheel()
+                );
+            }
+            Some(cmd @ ("press" | "release")) => {
+                let buttons = words
+                    .map(|b| b.parse().map(|b| StateChange::new(b, cmd == "press")))
+                    .collect::<Result<Vec<StateChange<Button>>, _>>()?;
+                input.extend(buttons);
+                dev.input(&input)?;
             }
-            Class::Mouse => {
-                let mut dev = Device::<Mouse>::open(path)?;
+            Some("move") => {
+                let x = words.next().unwrap_or("0").parse()?;
+                let y = words.next().unwrap_or("0").parse()?;
+                input.set_pointer((x, y));
+                dev.input(&input)?;
             }
-        },
+            Some("wheel") => {
+                let wheel = words.next().unwrap_or("0").parse()?;
+                input.set_wheel(wheel);
+                dev.input(&input)?;
+            }
+            Some(other) => {
+                println!("Unknown command: {}", other);
+            }
+        }
     }
 
     Ok(())
 }
+
+fn readline(rl: &mut Editor<Cli>) -> anyhow::Result<Option<String>> {
+    match rl.readline(">> ") {
+        Ok(line) => {
+            rl.add_history_entry(line.as_str());
+            Ok(Some(line))
+        }
+        Err(ReadlineError::Interrupted) => {
+            println!("CTRL-C");
+            Ok(None)
+        }
+        Err(ReadlineError::Eof) => {
+            println!("CTRL-D");
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
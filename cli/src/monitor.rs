@@ -0,0 +1,51 @@
+use hidg::{Class, Device, Keyboard, Led, StateChange};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// LED state change event
+#[derive(Serialize)]
+struct Event<'a> {
+    /// Seconds since UNIX epoch
+    time: f64,
+    /// LED name
+    name: &'a str,
+    #[serde(flatten)]
+    change: StateChange<Led>,
+}
+
+pub fn monitor(path: PathBuf, json: bool) -> anyhow::Result<()> {
+    let mut dev = Device::<Keyboard>::open(path)?;
+
+    let mut old = Keyboard.output();
+    let mut new = Keyboard.output();
+
+    loop {
+        dev.output(&mut new)?;
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+        for change in new.diff(&old) {
+            if json {
+                let event = Event {
+                    time: time.as_secs_f64(),
+                    name: change.as_ref(),
+                    change,
+                };
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                println!(
+                    "{}.{:03} {} {}",
+                    time.as_secs(),
+                    time.subsec_millis(),
+                    *change,
+                    if change.is_on() { "on" } else { "off" }
+                );
+            }
+        }
+
+        old = new;
+    }
+}
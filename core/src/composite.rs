@@ -0,0 +1,290 @@
+//! Keyboard and mouse combined
+
+use crate::{
+    IdReport, Key, KeyboardInput, KeyboardOutput, MouseInput, MouseInputChange, StateChange,
+};
+
+/// Input event of keyboard and mouse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositeEvent {
+    /// Key state change
+    Key(StateChange<Key>),
+    /// Mouse input change
+    Mouse(MouseInputChange),
+}
+
+impl From<StateChange<Key>> for CompositeEvent {
+    fn from(change: StateChange<Key>) -> Self {
+        Self::Key(change)
+    }
+}
+
+impl From<MouseInputChange> for CompositeEvent {
+    fn from(change: MouseInputChange) -> Self {
+        Self::Mouse(change)
+    }
+}
+
+/// Report which should be sent after event is applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositeTarget {
+    /// Keyboard report
+    Keyboard,
+    /// Mouse report
+    Mouse,
+}
+
+/// Current state of keyboard and mouse
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompositeInput {
+    keyboard: KeyboardInput,
+    mouse: MouseInput,
+    relative_pointer: bool,
+    relative_wheel: bool,
+}
+
+impl CompositeInput {
+    /// Get keyboard report
+    pub fn keyboard(&self) -> &KeyboardInput {
+        &self.keyboard
+    }
+
+    /// Get mouse report
+    pub fn mouse(&self) -> &MouseInput {
+        &self.mouse
+    }
+
+    /// Apply event and get report which should be sent
+    ///
+    /// Returns `None` when event does not change any report, for example repeated key press.
+    /// Relative pointer and wheel changes is put to mouse report as is,
+    /// call [`CompositeInput::sent`] after report is sent to reset them.
+    pub fn apply(&mut self, event: &CompositeEvent) -> Option<CompositeTarget> {
+        match event {
+            CompositeEvent::Key(change) => {
+                let old = self.keyboard;
+                self.keyboard.change_key(**change, change.state());
+                (AsRef::<[u8]>::as_ref(&old) != AsRef::<[u8]>::as_ref(&self.keyboard))
+                    .then_some(CompositeTarget::Keyboard)
+            }
+            CompositeEvent::Mouse(change) => {
+                let old = self.mouse;
+                match change {
+                    MouseInputChange::Button(change) => {
+                        self.mouse.change_button(**change, change.state())
+                    }
+                    MouseInputChange::Pointer(change) => {
+                        self.relative_pointer = change.is_relative();
+                        self.mouse.set_pointer(**change);
+                    }
+                    MouseInputChange::Wheel(change) => {
+                        self.relative_wheel = change.is_relative();
+                        self.mouse.set_wheel(**change);
+                    }
+                }
+                (AsRef::<[u8]>::as_ref(&old) != AsRef::<[u8]>::as_ref(&self.mouse))
+                    .then_some(CompositeTarget::Mouse)
+            }
+        }
+    }
+
+    /// Reset relative motion after report is sent
+    pub fn sent(&mut self, target: CompositeTarget) {
+        if target != CompositeTarget::Mouse {
+            return;
+        }
+        if self.relative_pointer {
+            self.mouse.set_pointer((0, 0));
+        }
+        if self.relative_wheel {
+            self.mouse.set_wheel(0);
+        }
+    }
+}
+
+enum Devices<K, M, S> {
+    Separate {
+        keyboard: K,
+        mouse: M,
+    },
+    Shared {
+        device: S,
+        keyboard_id: u8,
+        mouse_id: u8,
+    },
+}
+
+/// Report write which should be done after event is applied
+pub enum CompositeWrite<'d, K, M, S> {
+    /// Keyboard report to separate keyboard device
+    Keyboard(&'d mut K, KeyboardInput),
+    /// Mouse report to separate mouse device
+    Mouse(&'d mut M, MouseInput),
+    /// Keyboard report to shared device
+    SharedKeyboard(&'d mut S, IdReport<KeyboardInput>),
+    /// Mouse report to shared device
+    SharedMouse(&'d mut S, IdReport<MouseInput>),
+}
+
+/// Keyboard output report read
+pub enum CompositeRead<'d, K, S> {
+    /// Read from separate keyboard device
+    Keyboard(&'d mut K),
+    /// Read from shared device into report with keyboard ID
+    Shared(&'d mut S, IdReport<KeyboardOutput>),
+}
+
+/// State of keyboard and mouse together with devices which receive its reports
+///
+/// Decides which report goes to which device, so wrappers only do actual I/O.
+/// Keyboard and mouse is either separate devices `K` and `M`
+/// or share one device `S` which distinguishes reports by report IDs.
+pub struct CompositeState<K, M, S> {
+    devices: Devices<K, M, S>,
+    input: CompositeInput,
+}
+
+impl<K, M, S> CompositeState<K, M, S> {
+    /// Combine separate keyboard and mouse devices
+    pub fn separate(keyboard: K, mouse: M) -> Self {
+        Self {
+            devices: Devices::Separate { keyboard, mouse },
+            input: CompositeInput::default(),
+        }
+    }
+
+    /// Use one device which carries keyboard and mouse reports with given report IDs
+    pub fn shared(device: S, keyboard_id: u8, mouse_id: u8) -> Self {
+        Self {
+            devices: Devices::Shared {
+                device,
+                keyboard_id,
+                mouse_id,
+            },
+            input: CompositeInput::default(),
+        }
+    }
+
+    /// Get current state of keyboard and mouse
+    pub fn input(&self) -> &CompositeInput {
+        &self.input
+    }
+
+    /// Apply event and get report write
+    ///
+    /// Returns `None` when event does not change any report.
+    /// Relative motion is reset right away because write carries copy of report.
+    pub fn apply(&mut self, event: &CompositeEvent) -> Option<CompositeWrite<'_, K, M, S>> {
+        let target = self.input.apply(event)?;
+        let (keyboard_input, mouse_input) = (self.input.keyboard, self.input.mouse);
+        self.input.sent(target);
+        Some(match (&mut self.devices, target) {
+            (Devices::Separate { keyboard, .. }, CompositeTarget::Keyboard) => {
+                CompositeWrite::Keyboard(keyboard, keyboard_input)
+            }
+            (Devices::Separate { mouse, .. }, CompositeTarget::Mouse) => {
+                CompositeWrite::Mouse(mouse, mouse_input)
+            }
+            (
+                Devices::Shared {
+                    device,
+                    keyboard_id,
+                    ..
+                },
+                CompositeTarget::Keyboard,
+            ) => {
+                CompositeWrite::SharedKeyboard(device, IdReport::new(*keyboard_id, keyboard_input))
+            }
+            (
+                Devices::Shared {
+                    device, mouse_id, ..
+                },
+                CompositeTarget::Mouse,
+            ) => CompositeWrite::SharedMouse(device, IdReport::new(*mouse_id, mouse_input)),
+        })
+    }
+
+    /// Get keyboard output report read
+    pub fn read(&mut self) -> CompositeRead<'_, K, S> {
+        match &mut self.devices {
+            Devices::Separate { keyboard, .. } => CompositeRead::Keyboard(keyboard),
+            Devices::Shared {
+                device,
+                keyboard_id,
+                ..
+            } => CompositeRead::Shared(device, IdReport::new(*keyboard_id, Default::default())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Button, ValueChange};
+
+    #[test]
+    fn apply_events() {
+        let mut input = CompositeInput::default();
+
+        let event = StateChange::press(Key::A).into();
+        assert_eq!(input.apply(&event), Some(CompositeTarget::Keyboard));
+        assert_eq!(input.keyboard().pressed().collect::<Vec<_>>(), [Key::A]);
+        // key is already pressed
+        assert_eq!(input.apply(&event), None);
+
+        let event = MouseInputChange::Button(StateChange::press(Button::Primary)).into();
+        assert_eq!(input.apply(&event), Some(CompositeTarget::Mouse));
+        let event = MouseInputChange::Pointer(ValueChange::new((3, -1), true)).into();
+        input.apply(&event);
+        assert_eq!(input.mouse().pointer(), (3, -1));
+        input.sent(CompositeTarget::Mouse);
+        assert_eq!(input.mouse().pointer(), (0, 0));
+        assert_eq!(input.mouse().mods(), Button::Primary.into());
+
+        let event = MouseInputChange::Pointer(ValueChange::new((100, 200), false)).into();
+        input.apply(&event);
+        input.sent(CompositeTarget::Mouse);
+        assert_eq!(input.mouse().pointer(), (100, 200));
+
+        // absolute pointer is kept while relative wheel is reset
+        let event = MouseInputChange::Wheel(ValueChange::new(2, true)).into();
+        assert_eq!(input.apply(&event), Some(CompositeTarget::Mouse));
+        assert_eq!(input.mouse().wheel(), 2);
+        input.sent(CompositeTarget::Mouse);
+        assert_eq!(input.mouse().pointer(), (100, 200));
+        assert_eq!(input.mouse().wheel(), 0);
+
+        let event = MouseInputChange::Pointer(ValueChange::new((100, 200), false)).into();
+        assert_eq!(input.apply(&event), None);
+    }
+
+    #[test]
+    fn route_writes() {
+        let mut state = CompositeState::<u8, u8, u8>::shared(0, 1, 2);
+        let event = StateChange::press(Key::A).into();
+        let Some(CompositeWrite::SharedKeyboard(_, report)) = state.apply(&event) else {
+            panic!("keyboard report is expected");
+        };
+        assert_eq!((report.id(), report.pressed().count()), (1, 1));
+        let event = MouseInputChange::Pointer(ValueChange::new((3, -1), true)).into();
+        let Some(CompositeWrite::SharedMouse(_, report)) = state.apply(&event) else {
+            panic!("mouse report is expected");
+        };
+        assert_eq!((report.id(), report.pointer()), (2, (3, -1)));
+        // relative motion is reset once report is taken
+        assert_eq!(state.input().mouse().pointer(), (0, 0));
+        let CompositeRead::Shared(_, report) = state.read() else {
+            panic!("shared read is expected");
+        };
+        assert_eq!(report.id(), 1);
+
+        let mut state = CompositeState::<u8, u8, u8>::separate(0, 1);
+        assert!(matches!(
+            state.apply(&event),
+            Some(CompositeWrite::Mouse(&mut 1, _))
+        ));
+        assert!(matches!(state.read(), CompositeRead::Keyboard(&mut 0)));
+        let event = StateChange::release(Key::A).into();
+        assert!(state.apply(&event).is_none());
+    }
+}
@@ -0,0 +1,296 @@
+//! Connection state tracking
+
+use std::{
+    future::poll_fn,
+    io,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{Error, Result};
+
+/// Connection state of device
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// Device file is not opened
+    Closed,
+    /// Device file is opened but host is not connected or suspended
+    Disconnected,
+    /// Host is connected and receives reports
+    Connected,
+}
+
+struct Shared {
+    state: ConnectionState,
+    version: u64,
+    wakers: Vec<Waker>,
+}
+
+/// Observable connection state
+///
+/// Clones share the same state.
+#[derive(Clone)]
+pub struct ConnectionWatch {
+    shared: Arc<(Mutex<Shared>, Condvar)>,
+}
+
+impl ConnectionWatch {
+    /// Create connection state with initial value
+    pub fn new(state: ConnectionState) -> Self {
+        Self {
+            shared: Arc::new((
+                Mutex::new(Shared {
+                    state,
+                    version: 0,
+                    wakers: Vec::new(),
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+
+    fn shared(&self) -> MutexGuard<'_, Shared> {
+        self.shared
+            .0
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Get current state
+    pub fn get(&self) -> ConnectionState {
+        self.shared().state
+    }
+
+    /// Set current state
+    ///
+    /// Subscribers is notified only when state actually changed.
+    pub fn set(&self, state: ConnectionState) {
+        let mut shared = self.shared();
+        if shared.state == state {
+            return;
+        }
+        shared.state = state;
+        shared.version += 1;
+        for waker in shared.wakers.drain(..) {
+            waker.wake();
+        }
+        self.shared.1.notify_all();
+    }
+
+    /// Subscribe to state changes
+    pub fn subscribe(&self) -> ConnectionWatcher {
+        ConnectionWatcher {
+            version: self.shared().version,
+            watch: self.clone(),
+        }
+    }
+}
+
+/// Subscriber of connection state changes
+pub struct ConnectionWatcher {
+    watch: ConnectionWatch,
+    version: u64,
+}
+
+impl ConnectionWatcher {
+    /// Get current state
+    pub fn get(&self) -> ConnectionState {
+        self.watch.get()
+    }
+
+    /// Wait for state change blocking current thread
+    pub fn wait(&mut self) -> ConnectionState {
+        let (_, condvar) = &*self.watch.shared;
+        let mut shared = self.watch.shared();
+        while shared.version == self.version {
+            shared = condvar
+                .wait(shared)
+                .unwrap_or_else(|error| error.into_inner());
+        }
+        self.version = shared.version;
+        shared.state
+    }
+
+    /// Poll for state change
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<ConnectionState> {
+        let mut shared = self.watch.shared();
+        if shared.version != self.version {
+            self.version = shared.version;
+            return Poll::Ready(shared.state);
+        }
+        if !shared
+            .wakers
+            .iter()
+            .any(|waker| waker.will_wake(cx.waker()))
+        {
+            shared.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Wait for state change asynchronously
+    pub async fn changed(&mut self) -> ConnectionState {
+        poll_fn(|cx| self.poll_changed(cx)).await
+    }
+}
+
+/// Reconnection state machine
+///
+/// Holds opened device and current input report and tracks connection state by transfer results.
+/// Device I/O is done by wrappers, so same logic serves blocking and asynchronous devices.
+pub struct Reconnect<D, I> {
+    device: Option<D>,
+    input: I,
+    watch: ConnectionWatch,
+}
+
+impl<D, I> Reconnect<D, I> {
+    /// Create closed state with initial input report
+    pub fn new(input: I) -> Self {
+        Self {
+            device: None,
+            input,
+            watch: ConnectionWatch::new(ConnectionState::Closed),
+        }
+    }
+
+    /// Get current connection state
+    pub fn state(&self) -> ConnectionState {
+        self.watch.get()
+    }
+
+    /// Subscribe to connection state changes
+    pub fn subscribe(&self) -> ConnectionWatcher {
+        self.watch.subscribe()
+    }
+
+    /// Get current input report
+    pub fn current(&self) -> &I {
+        &self.input
+    }
+
+    /// Replace current input report
+    pub fn set_current(&mut self, input: I) {
+        self.input = input;
+    }
+
+    /// Check whether device is opened
+    pub fn is_open(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// Store result of opening device
+    pub fn opened(&mut self, result: Result<D>) -> Result<()> {
+        match result {
+            Ok(device) => {
+                self.device = Some(device);
+                self.watch.set(ConnectionState::Disconnected);
+                Ok(())
+            }
+            Err(error) => {
+                self.watch.set(ConnectionState::Closed);
+                Err(error)
+            }
+        }
+    }
+
+    /// Get opened device together with current input report
+    pub fn parts_mut(&mut self) -> Option<(&mut D, &I)> {
+        self.device.as_mut().map(|device| (device, &self.input))
+    }
+
+    /// Update state by result of transfer
+    ///
+    /// Device is dropped on errors which cannot be recovered without re-opening it.
+    pub fn update<T>(&mut self, result: Result<T>) -> Result<T> {
+        match &result {
+            Ok(_) => self.watch.set(ConnectionState::Connected),
+            Err(Error::NotConnected) => self.watch.set(ConnectionState::Disconnected),
+            // host is still here but does not poll endpoint in time
+            Err(Error::Busy | Error::ShortWrite { .. } | Error::ShortRead { .. }) => {}
+            Err(Error::Io(error))
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::TimedOut
+                        | io::ErrorKind::WouldBlock
+                        | io::ErrorKind::Interrupted
+                ) => {}
+            Err(_) => {
+                self.device = None;
+                self.watch.set(ConnectionState::Closed);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reconnect_states() {
+        let mut reconnect = Reconnect::<(), u8>::new(0);
+        assert_eq!(reconnect.state(), ConnectionState::Closed);
+        assert!(reconnect.parts_mut().is_none());
+
+        assert!(reconnect.opened(Err(Error::NotConnected)).is_err());
+        assert_eq!(reconnect.state(), ConnectionState::Closed);
+        reconnect.opened(Ok(())).unwrap();
+        assert_eq!(reconnect.state(), ConnectionState::Disconnected);
+
+        reconnect.set_current(1);
+        assert_eq!(reconnect.parts_mut(), Some((&mut (), &1)));
+        reconnect.update(Ok(())).unwrap();
+        assert_eq!(reconnect.state(), ConnectionState::Connected);
+        reconnect
+            .update::<()>(Err(Error::NotConnected))
+            .unwrap_err();
+        assert_eq!(reconnect.state(), ConnectionState::Disconnected);
+
+        // transient errors keep device opened
+        reconnect.update::<()>(Err(Error::Busy)).unwrap_err();
+        for kind in [
+            io::ErrorKind::TimedOut,
+            io::ErrorKind::WouldBlock,
+            io::ErrorKind::Interrupted,
+        ] {
+            reconnect.update::<()>(Err(kind.into())).unwrap_err();
+        }
+        assert!(reconnect.is_open());
+        assert_eq!(reconnect.state(), ConnectionState::Disconnected);
+
+        let broken = io::Error::from(io::ErrorKind::BrokenPipe);
+        reconnect.update::<()>(Err(Error::Io(broken))).unwrap_err();
+        assert!(!reconnect.is_open());
+        assert_eq!(reconnect.state(), ConnectionState::Closed);
+    }
+
+    #[test]
+    fn watch_changes() {
+        let watch = ConnectionWatch::new(ConnectionState::Closed);
+        let mut watcher = watch.subscribe();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert_eq!(watcher.get(), ConnectionState::Closed);
+        assert!(watcher.poll_changed(&mut cx).is_pending());
+
+        // same state is not a change
+        watch.set(ConnectionState::Closed);
+        assert!(watcher.poll_changed(&mut cx).is_pending());
+
+        watch.set(ConnectionState::Connected);
+        assert_eq!(
+            watcher.poll_changed(&mut cx),
+            Poll::Ready(ConnectionState::Connected)
+        );
+        assert!(watcher.poll_changed(&mut cx).is_pending());
+
+        let thread = {
+            let watch = watch.clone();
+            std::thread::spawn(move || watch.set(ConnectionState::Disconnected))
+        };
+        assert_eq!(watcher.wait(), ConnectionState::Disconnected);
+        thread.join().unwrap();
+    }
+}
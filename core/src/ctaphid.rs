@@ -0,0 +1,734 @@
+//! CTAPHID framing of FIDO authenticators
+//!
+//! Messages is exchanged over [`CtapHid`] class as fixed size packets. Initialization packet
+//! carries channel ID, command and total length of message, following continuation packets
+//! carries channel ID and sequence number. This module implements framing and dispatching
+//! without doing any I/O, so it can be driven by any device implementation.
+
+use std::time::{Duration, Instant};
+
+use bitflags::bitflags;
+
+use crate::RawHid;
+
+/// Length of CTAPHID packet
+pub const PACKET_LEN: usize = 64;
+
+/// Data length of initialization packet
+pub const INIT_DATA_LEN: usize = PACKET_LEN - 7;
+
+/// Data length of continuation packet
+pub const CONT_DATA_LEN: usize = PACKET_LEN - 5;
+
+/// Maximum length of message data
+pub const MAX_MESSAGE_LEN: usize = INIT_DATA_LEN + 0x80 * CONT_DATA_LEN;
+
+/// Channel used to allocate new channels
+pub const BROADCAST_CID: u32 = 0xffff_ffff;
+
+/// Version of CTAPHID protocol
+pub const PROTOCOL_VERSION: u8 = 2;
+
+/// Default time to wait for continuation packets of message
+pub const TRANSACTION_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Interval of sending KEEPALIVE messages while processing request
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// CTAPHID packet
+pub type Packet = [u8; PACKET_LEN];
+
+/// Raw HID class used by FIDO authenticators
+pub type CtapHid = RawHid<PACKET_LEN, PACKET_LEN>;
+
+/// CTAPHID command
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    /// Echo data back
+    Ping,
+    /// CTAP1/U2F APDU message
+    Msg,
+    /// Exclusive channel lock
+    Lock,
+    /// Channel allocation and synchronization
+    Init,
+    /// Visual identification of authenticator
+    Wink,
+    /// CTAP2 CBOR message
+    Cbor,
+    /// Cancel processing of current request
+    Cancel,
+    /// Request is still processed
+    Keepalive,
+    /// Error response
+    Error,
+    /// Vendor specific command (`0x40..=0x7f`) or unknown one
+    Other(u8),
+}
+
+impl From<u8> for Command {
+    /// Convert command byte with or without initialization packet bit
+    fn from(raw: u8) -> Self {
+        match raw & 0x7f {
+            0x01 => Self::Ping,
+            0x03 => Self::Msg,
+            0x04 => Self::Lock,
+            0x06 => Self::Init,
+            0x08 => Self::Wink,
+            0x10 => Self::Cbor,
+            0x11 => Self::Cancel,
+            0x3b => Self::Keepalive,
+            0x3f => Self::Error,
+            raw => Self::Other(raw),
+        }
+    }
+}
+
+impl From<Command> for u8 {
+    fn from(command: Command) -> Self {
+        match command {
+            Command::Ping => 0x01,
+            Command::Msg => 0x03,
+            Command::Lock => 0x04,
+            Command::Init => 0x06,
+            Command::Wink => 0x08,
+            Command::Cbor => 0x10,
+            Command::Cancel => 0x11,
+            Command::Keepalive => 0x3b,
+            Command::Error => 0x3f,
+            Command::Other(raw) => raw & 0x7f,
+        }
+    }
+}
+
+impl Command {
+    /// Check that command is vendor specific
+    pub fn is_vendor(&self) -> bool {
+        matches!(self, Self::Other(0x40..=0x7f))
+    }
+}
+
+/// CTAPHID error code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ErrorCode {
+    /// Command is not supported
+    InvalidCmd = 0x01,
+    /// Invalid parameter of command
+    InvalidPar = 0x02,
+    /// Invalid message length
+    InvalidLen = 0x03,
+    /// Invalid sequence number of continuation packet
+    InvalidSeq = 0x04,
+    /// Message is not completed in time
+    MsgTimeout = 0x05,
+    /// Other channel is processed
+    ChannelBusy = 0x06,
+    /// Command requires channel lock
+    LockRequired = 0x0a,
+    /// Invalid channel ID
+    InvalidChannel = 0x0b,
+    /// Unspecified error
+    Other = 0x7f,
+}
+
+impl From<ErrorCode> for u8 {
+    fn from(code: ErrorCode) -> Self {
+        code as _
+    }
+}
+
+/// Status of KEEPALIVE message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum KeepaliveStatus {
+    /// Authenticator is processing request
+    Processing = 0x01,
+    /// Authenticator is waiting for user presence
+    UpNeeded = 0x02,
+}
+
+bitflags! {
+    /// Capabilities reported in INIT response
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Capabilities: u8 {
+        /// WINK command is supported
+        const Wink = 0x01;
+        /// CBOR command is supported
+        const Cbor = 0x04;
+        /// MSG command is not supported
+        const Nmsg = 0x08;
+    }
+}
+
+/// Get channel ID of packet
+pub fn packet_channel(packet: &Packet) -> u32 {
+    u32::from_be_bytes([packet[0], packet[1], packet[2], packet[3]])
+}
+
+/// Get command of packet
+///
+/// Returns `None` for continuation packets.
+pub fn packet_command(packet: &Packet) -> Option<Command> {
+    if packet[4] & 0x80 != 0 {
+        Some(packet[4].into())
+    } else {
+        None
+    }
+}
+
+/// CTAPHID message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    cid: u32,
+    command: Command,
+    data: Vec<u8>,
+}
+
+impl Message {
+    /// Create message
+    ///
+    /// Fails with [`ErrorCode::InvalidLen`] when data is longer than [`MAX_MESSAGE_LEN`].
+    pub fn new(
+        cid: u32,
+        command: Command,
+        data: impl Into<Vec<u8>>,
+    ) -> core::result::Result<Self, ChannelError> {
+        let data = data.into();
+        if data.len() > MAX_MESSAGE_LEN {
+            return Err(ChannelError::new(cid, ErrorCode::InvalidLen));
+        }
+        Ok(Self { cid, command, data })
+    }
+
+    /// Create error message
+    pub fn error(cid: u32, code: ErrorCode) -> Self {
+        Self {
+            cid,
+            command: Command::Error,
+            data: vec![code.into()],
+        }
+    }
+
+    /// Create keepalive message
+    pub fn keepalive(cid: u32, status: KeepaliveStatus) -> Self {
+        Self {
+            cid,
+            command: Command::Keepalive,
+            data: vec![status as u8],
+        }
+    }
+
+    /// Get channel ID
+    pub fn cid(&self) -> u32 {
+        self.cid
+    }
+
+    /// Get command
+    pub fn command(&self) -> Command {
+        self.command
+    }
+
+    /// Get data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get data by value
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Split message into packets
+    pub fn packets(&self) -> Packets<'_> {
+        Packets {
+            message: self,
+            offset: 0,
+            seq: None,
+        }
+    }
+}
+
+/// Iterator over packets of message
+pub struct Packets<'m> {
+    message: &'m Message,
+    offset: usize,
+    seq: Option<u8>,
+}
+
+impl Iterator for Packets<'_> {
+    type Item = Packet;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = &self.message.data;
+        let mut packet = [0; PACKET_LEN];
+        packet[..4].copy_from_slice(&self.message.cid.to_be_bytes());
+        let (header, len) = match self.seq {
+            None => {
+                packet[4] = 0x80 | u8::from(self.message.command);
+                packet[5..7].copy_from_slice(&(data.len() as u16).to_be_bytes());
+                self.seq = Some(0);
+                (7, INIT_DATA_LEN)
+            }
+            Some(_) if self.offset >= data.len() => return None,
+            Some(seq) => {
+                packet[4] = seq;
+                self.seq = Some(seq + 1);
+                (5, CONT_DATA_LEN)
+            }
+        };
+        let len = len.min(data.len() - self.offset);
+        packet[header..header + len].copy_from_slice(&data[self.offset..self.offset + len]);
+        self.offset += len;
+        Some(packet)
+    }
+}
+
+/// Error which should be reported to host on given channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelError {
+    cid: u32,
+    code: ErrorCode,
+}
+
+impl ChannelError {
+    /// Create channel error
+    pub fn new(cid: u32, code: ErrorCode) -> Self {
+        Self { cid, code }
+    }
+
+    /// Get channel ID
+    pub fn cid(&self) -> u32 {
+        self.cid
+    }
+
+    /// Get error code
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// Create error message
+    pub fn message(&self) -> Message {
+        Message::error(self.cid, self.code)
+    }
+}
+
+struct Transaction {
+    message: Message,
+    len: usize,
+    seq: u8,
+    deadline: Instant,
+}
+
+/// Reassembly of messages from packets
+///
+/// Only one message is received at a time, packets from other channels is rejected
+/// until message is completed or timed out.
+pub struct Assembler {
+    timeout: Duration,
+    transaction: Option<Transaction>,
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        Self::new(TRANSACTION_TIMEOUT)
+    }
+}
+
+impl Assembler {
+    /// Create assembler with given timeout of continuation packets
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            transaction: None,
+        }
+    }
+
+    /// Get channel ID of incomplete message
+    pub fn pending(&self) -> Option<u32> {
+        self.transaction
+            .as_ref()
+            .map(|transaction| transaction.message.cid)
+    }
+
+    /// Get time when incomplete message expires
+    pub fn deadline(&self) -> Option<Instant> {
+        self.transaction
+            .as_ref()
+            .map(|transaction| transaction.deadline)
+    }
+
+    /// Drop incomplete message if it is expired at given time
+    pub fn expire(&mut self, now: Instant) -> Option<ChannelError> {
+        if self.deadline()? > now {
+            return None;
+        }
+        let transaction = self.transaction.take()?;
+        Some(ChannelError::new(
+            transaction.message.cid,
+            ErrorCode::MsgTimeout,
+        ))
+    }
+
+    /// Put packet received at given time
+    ///
+    /// Returns message when it is completed by packet.
+    pub fn push(
+        &mut self,
+        packet: &Packet,
+        now: Instant,
+    ) -> core::result::Result<Option<Message>, ChannelError> {
+        let cid = packet_channel(packet);
+        if cid == 0 {
+            return Err(ChannelError::new(cid, ErrorCode::InvalidChannel));
+        }
+        let Some(command) = packet_command(packet) else {
+            return self.push_continuation(cid, packet, now);
+        };
+        if let Some(pending) = self.pending() {
+            if pending != cid {
+                return Err(ChannelError::new(cid, ErrorCode::ChannelBusy));
+            }
+            self.transaction = None;
+            // INIT resynchronizes channel
+            if command != Command::Init {
+                return Err(ChannelError::new(cid, ErrorCode::InvalidSeq));
+            }
+        }
+        let len = u16::from_be_bytes([packet[5], packet[6]]) as usize;
+        if len > MAX_MESSAGE_LEN {
+            return Err(ChannelError::new(cid, ErrorCode::InvalidLen));
+        }
+        let data = &packet[7..7 + len.min(INIT_DATA_LEN)];
+        let mut message = Message {
+            cid,
+            command,
+            data: Vec::with_capacity(len),
+        };
+        message.data.extend_from_slice(data);
+        if message.data.len() == len {
+            return Ok(Some(message));
+        }
+        self.transaction = Some(Transaction {
+            message,
+            len,
+            seq: 0,
+            deadline: now + self.timeout,
+        });
+        Ok(None)
+    }
+
+    fn push_continuation(
+        &mut self,
+        cid: u32,
+        packet: &Packet,
+        now: Instant,
+    ) -> core::result::Result<Option<Message>, ChannelError> {
+        // spurious continuation packets is ignored
+        let Some(transaction) = self.transaction.as_mut() else {
+            return Ok(None);
+        };
+        if transaction.message.cid != cid {
+            return Err(ChannelError::new(cid, ErrorCode::ChannelBusy));
+        }
+        if packet[4] != transaction.seq {
+            self.transaction = None;
+            return Err(ChannelError::new(cid, ErrorCode::InvalidSeq));
+        }
+        let data = &mut transaction.message.data;
+        let len = (transaction.len - data.len()).min(CONT_DATA_LEN);
+        data.extend_from_slice(&packet[5..5 + len]);
+        if data.len() < transaction.len {
+            transaction.seq += 1;
+            transaction.deadline = now + self.timeout;
+            return Ok(None);
+        }
+        Ok(self
+            .transaction
+            .take()
+            .map(|transaction| transaction.message))
+    }
+}
+
+/// Reporting of progress while request is processed
+pub trait Progress {
+    /// Send KEEPALIVE message to host
+    ///
+    /// Returns `true` when host cancelled request. Should be called at least once per
+    /// [`KEEPALIVE_INTERVAL`] during long operations like waiting for user presence.
+    fn keepalive(&mut self, status: KeepaliveStatus) -> bool;
+}
+
+/// Handler of CTAP requests
+pub trait Handler {
+    /// Get version of authenticator (major, minor, build)
+    fn version(&self) -> [u8; 3] {
+        [0; 3]
+    }
+
+    /// Get supported capabilities
+    fn capabilities(&self) -> Capabilities;
+
+    /// Identify authenticator visually
+    ///
+    /// Called only when [`Capabilities::Wink`] is reported.
+    fn wink(&mut self) {}
+
+    /// Handle MSG, CBOR or vendor specific request
+    ///
+    /// Returns data of response which is sent with same command.
+    fn handle(
+        &mut self,
+        command: Command,
+        request: &[u8],
+        progress: &mut dyn Progress,
+    ) -> core::result::Result<Vec<u8>, ErrorCode>;
+}
+
+/// Dispatcher of requests to handler
+///
+/// Processes INIT, PING, WINK and CANCEL commands itself and forwards CTAP requests to handler.
+pub struct Dispatcher<H> {
+    handler: H,
+    last_cid: u32,
+}
+
+impl<H: Handler> Dispatcher<H> {
+    /// Create dispatcher
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            last_cid: 0,
+        }
+    }
+
+    /// Get reference to handler
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Get mutable reference to handler
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Get handler
+    pub fn into_handler(self) -> H {
+        self.handler
+    }
+
+    fn allocate(&mut self) -> u32 {
+        self.last_cid = match self.last_cid.wrapping_add(1) {
+            0 | BROADCAST_CID => 1,
+            cid => cid,
+        };
+        self.last_cid
+    }
+
+    /// Process request and produce response
+    ///
+    /// Returns `None` when request has no response (CANCEL outside of processing).
+    pub fn dispatch(&mut self, request: &Message, progress: &mut dyn Progress) -> Option<Message> {
+        let cid = request.cid;
+        let capabilities = self.handler.capabilities();
+        let result = match request.command {
+            _ if cid == BROADCAST_CID && request.command != Command::Init => {
+                Err(ErrorCode::InvalidChannel)
+            }
+            Command::Init => self.init(request, capabilities),
+            Command::Ping => Ok(request.data.clone()),
+            Command::Wink if capabilities.contains(Capabilities::Wink) => {
+                self.handler.wink();
+                Ok(Vec::new())
+            }
+            Command::Cancel => return None,
+            Command::Msg if !capabilities.contains(Capabilities::Nmsg) => {
+                self.handle(request, progress)
+            }
+            Command::Cbor if capabilities.contains(Capabilities::Cbor) => {
+                self.handle(request, progress)
+            }
+            command if command.is_vendor() => self.handle(request, progress),
+            _ => Err(ErrorCode::InvalidCmd),
+        };
+        Some(match result {
+            Ok(data) => Message::new(cid, request.command, data)
+                .unwrap_or_else(|error| Message::error(cid, error.code())),
+            Err(code) => Message::error(cid, code),
+        })
+    }
+
+    fn init(
+        &mut self,
+        request: &Message,
+        capabilities: Capabilities,
+    ) -> core::result::Result<Vec<u8>, ErrorCode> {
+        if request.data.len() != 8 {
+            return Err(ErrorCode::InvalidLen);
+        }
+        let cid = if request.cid == BROADCAST_CID {
+            self.allocate()
+        } else {
+            request.cid
+        };
+        let mut data = request.data.clone();
+        data.extend_from_slice(&cid.to_be_bytes());
+        data.push(PROTOCOL_VERSION);
+        data.extend_from_slice(&self.handler.version());
+        data.push(capabilities.bits());
+        Ok(data)
+    }
+
+    fn handle(
+        &mut self,
+        request: &Message,
+        progress: &mut dyn Progress,
+    ) -> core::result::Result<Vec<u8>, ErrorCode> {
+        let data = self
+            .handler
+            .handle(request.command, &request.data, progress)?;
+        if data.len() > MAX_MESSAGE_LEN {
+            return Err(ErrorCode::Other);
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Echo;
+
+    impl Handler for Echo {
+        fn capabilities(&self) -> Capabilities {
+            Capabilities::Cbor | Capabilities::Nmsg
+        }
+
+        fn handle(
+            &mut self,
+            _command: Command,
+            request: &[u8],
+            progress: &mut dyn Progress,
+        ) -> core::result::Result<Vec<u8>, ErrorCode> {
+            if progress.keepalive(KeepaliveStatus::UpNeeded) {
+                return Err(ErrorCode::Other);
+            }
+            Ok(request.iter().rev().copied().collect())
+        }
+    }
+
+    struct Cancelled(bool);
+
+    impl Progress for Cancelled {
+        fn keepalive(&mut self, _status: KeepaliveStatus) -> bool {
+            self.0
+        }
+    }
+
+    #[test]
+    fn fragmentation() {
+        let data: Vec<u8> = (0..200).map(|byte| byte as u8).collect();
+        let message = Message::new(0x01020304, Command::Cbor, data.clone()).unwrap();
+        let packets: Vec<_> = message.packets().collect();
+        assert_eq!(packets.len(), 4);
+        assert_eq!(packets[0][..7], [1, 2, 3, 4, 0x90, 0, 200]);
+        assert_eq!(packets[1][..5], [1, 2, 3, 4, 0]);
+        assert_eq!(packets[3][4], 2);
+
+        let mut assembler = Assembler::default();
+        let now = Instant::now();
+        for packet in &packets[..3] {
+            assert_eq!(assembler.push(packet, now), Ok(None));
+        }
+        assert_eq!(assembler.pending(), Some(0x01020304));
+        assert_eq!(assembler.push(&packets[3], now), Ok(Some(message)));
+        assert_eq!(assembler.pending(), None);
+
+        let empty = Message::new(1, Command::Ping, []).unwrap();
+        assert_eq!(empty.packets().count(), 1);
+        let full = Message::new(1, Command::Ping, vec![0; MAX_MESSAGE_LEN]).unwrap();
+        assert_eq!(full.packets().last().unwrap()[4], 0x7f);
+        assert_eq!(
+            Message::new(1, Command::Ping, vec![0; MAX_MESSAGE_LEN + 1]),
+            Err(ChannelError::new(1, ErrorCode::InvalidLen))
+        );
+    }
+
+    #[test]
+    fn reassembly_errors() {
+        let message = Message::new(1, Command::Msg, vec![0; 100]).unwrap();
+        let packets: Vec<_> = message.packets().collect();
+        let other = Message::new(2, Command::Ping, []).unwrap();
+        let other = other.packets().next().unwrap();
+        let mut assembler = Assembler::default();
+        let now = Instant::now();
+
+        assert_eq!(assembler.push(&packets[1], now), Ok(None));
+        assert_eq!(assembler.push(&packets[0], now), Ok(None));
+        assert_eq!(
+            assembler.push(&other, now),
+            Err(ChannelError::new(2, ErrorCode::ChannelBusy))
+        );
+        let mut continuation = packets[1];
+        continuation[..4].copy_from_slice(&2u32.to_be_bytes());
+        assert_eq!(
+            assembler.push(&continuation, now),
+            Err(ChannelError::new(2, ErrorCode::ChannelBusy))
+        );
+        assert_eq!(
+            assembler.push(&packets[0], now),
+            Err(ChannelError::new(1, ErrorCode::InvalidSeq))
+        );
+
+        assert_eq!(assembler.push(&packets[0], now), Ok(None));
+        assert_eq!(assembler.expire(now), None);
+        assert_eq!(
+            assembler.expire(now + TRANSACTION_TIMEOUT),
+            Some(ChannelError::new(1, ErrorCode::MsgTimeout))
+        );
+
+        let mut long = packets[0];
+        long[5..7].copy_from_slice(&u16::MAX.to_be_bytes());
+        assert_eq!(
+            assembler.push(&long, now),
+            Err(ChannelError::new(1, ErrorCode::InvalidLen))
+        );
+    }
+
+    #[test]
+    fn dispatching() {
+        let mut dispatcher = Dispatcher::new(Echo);
+        let mut progress = Cancelled(false);
+        let nonce = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        let init = Message::new(BROADCAST_CID, Command::Init, nonce).unwrap();
+        let response = dispatcher.dispatch(&init, &mut progress).unwrap();
+        assert_eq!(response.cid(), BROADCAST_CID);
+        assert_eq!(response.data()[..8], nonce);
+        assert_eq!(response.data()[8..], [0, 0, 0, 1, 2, 0, 0, 0, 0x0c]);
+        let response = dispatcher.dispatch(&init, &mut progress).unwrap();
+        assert_eq!(response.data()[8..12], [0, 0, 0, 2]);
+
+        let cbor = Message::new(1, Command::Cbor, [1, 2, 3]).unwrap();
+        let response = dispatcher.dispatch(&cbor, &mut progress).unwrap();
+        assert_eq!(response, Message::new(1, Command::Cbor, [3, 2, 1]).unwrap());
+
+        let msg = Message::new(1, Command::Msg, [1]).unwrap();
+        let response = dispatcher.dispatch(&msg, &mut progress).unwrap();
+        assert_eq!(response, Message::error(1, ErrorCode::InvalidCmd));
+
+        let ping = Message::new(BROADCAST_CID, Command::Ping, [1]).unwrap();
+        let response = dispatcher.dispatch(&ping, &mut progress).unwrap();
+        assert_eq!(
+            response,
+            Message::error(BROADCAST_CID, ErrorCode::InvalidChannel)
+        );
+
+        let cancel = Message::new(1, Command::Cancel, []).unwrap();
+        assert_eq!(dispatcher.dispatch(&cancel, &mut progress), None);
+
+        let response = dispatcher.dispatch(&cbor, &mut Cancelled(true)).unwrap();
+        assert_eq!(response, Message::error(1, ErrorCode::Other));
+    }
+}
@@ -44,6 +44,7 @@ where
 impl<L: Class, R: Class> Class for Either<L, R> {
     type Input = EitherReport<L::Input, R::Input>;
     type Output = EitherReport<L::Output, R::Output>;
+    type Feature = EitherReport<L::Feature, R::Feature>;
 
     fn input(&self) -> Self::Input {
         EitherReport::new(
@@ -60,4 +61,12 @@ impl<L: Class, R: Class> Class for Either<L, R> {
                 .map_right(|class| class.output()),
         )
     }
+
+    fn feature(&self) -> Self::Feature {
+        EitherReport::new(
+            self.as_ref()
+                .map_left(|class| class.feature())
+                .map_right(|class| class.feature()),
+        )
+    }
 }
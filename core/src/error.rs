@@ -0,0 +1,182 @@
+//! Error type
+
+use std::{io, path::PathBuf};
+
+/// Result type
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// HID gadget error
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Device cannot be opened
+    Open {
+        /// Device path
+        path: PathBuf,
+        /// Underlying error
+        source: io::Error,
+    },
+    /// Host is not connected or suspended (`ESHUTDOWN`)
+    NotConnected,
+    /// Endpoint is busy because host does not poll it (`EAGAIN` on write)
+    Busy,
+    /// Report is written partially
+    ShortWrite {
+        /// Number of bytes written
+        written: usize,
+        /// Size of report
+        expected: usize,
+    },
+    /// Report is read partially
+    ShortRead {
+        /// Number of bytes read
+        read: usize,
+        /// Size of report
+        expected: usize,
+    },
+    /// Report contents is invalid
+    InvalidReport(String),
+    /// Other I/O error
+    Io(io::Error),
+}
+
+impl Error {
+    /// Create device open error
+    pub fn open(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self::Open {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Create invalid report error
+    pub fn invalid_report(reason: impl Into<String>) -> Self {
+        Self::InvalidReport(reason.into())
+    }
+
+    /// Get corresponding I/O error kind
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::Open { source, .. } => source.kind(),
+            Self::NotConnected => io::ErrorKind::NotConnected,
+            Self::Busy => io::ErrorKind::WouldBlock,
+            Self::ShortWrite { .. } => io::ErrorKind::WriteZero,
+            Self::ShortRead { .. } => io::ErrorKind::UnexpectedEof,
+            Self::InvalidReport(_) => io::ErrorKind::InvalidData,
+            Self::Io(error) => error.kind(),
+        }
+    }
+
+    /// Get OS error code if any
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Self::Open { source, .. } => source.raw_os_error(),
+            Self::NotConnected => Some(libc::ESHUTDOWN),
+            Self::Busy => Some(libc::EAGAIN),
+            Self::Io(error) => error.raw_os_error(),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Open { source, .. } => Some(source),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Open { path, source } => {
+                write!(f, "Unable to open device {}: {source}", path.display())
+            }
+            Self::NotConnected => f.write_str("Host is not connected"),
+            Self::Busy => f.write_str("Endpoint is busy"),
+            Self::ShortWrite { written, expected } => {
+                write!(
+                    f,
+                    "Report is written partially ({written} of {expected} bytes)"
+                )
+            }
+            Self::ShortRead { read, expected } => {
+                write!(f, "Report is read partially ({read} of {expected} bytes)")
+            }
+            Self::InvalidReport(reason) => write!(f, "Invalid report: {reason}"),
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        if error.raw_os_error() == Some(libc::ESHUTDOWN) {
+            return Self::NotConnected;
+        }
+        // unwrap error which was converted to I/O error before
+        if error.get_ref().is_some_and(|inner| inner.is::<Self>()) {
+            if let Ok(inner) = error.into_inner().unwrap().downcast::<Self>() {
+                return *inner;
+            }
+            unreachable!();
+        }
+        Self::Io(error)
+    }
+}
+
+impl From<io::ErrorKind> for Error {
+    fn from(kind: io::ErrorKind) -> Self {
+        Self::Io(kind.into())
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => error,
+            error => io::Error::new(error.kind(), error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn io_conversions() {
+        let error = Error::from(io::Error::from_raw_os_error(libc::ESHUTDOWN));
+        assert!(matches!(error, Error::NotConnected));
+        assert_eq!(error.kind(), io::ErrorKind::NotConnected);
+
+        // no pending output report is not busy endpoint
+        let error = Error::from(io::Error::from_raw_os_error(libc::EAGAIN));
+        assert!(matches!(error, Error::Io(_)));
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+        assert!(std::error::Error::source(&error).unwrap().is::<io::Error>());
+
+        let error = Error::from(io::Error::from_raw_os_error(libc::EIO));
+        assert_eq!(error.raw_os_error(), Some(libc::EIO));
+
+        let error = io::Error::from(Error::ShortWrite {
+            written: 1,
+            expected: 8,
+        });
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+        assert!(matches!(
+            Error::from(error),
+            Error::ShortWrite {
+                written: 1,
+                expected: 8
+            }
+        ));
+
+        let error = Error::open("/dev/hidg0", io::ErrorKind::NotFound.into());
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().contains("/dev/hidg0"));
+    }
+}
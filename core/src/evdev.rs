@@ -0,0 +1,370 @@
+//! Linux input event codes conversions
+//!
+//! Codes from `linux/input-event-codes.h` are wrapped into newtypes to avoid confusion with
+//! HID usages which is used as raw values of code enums.
+
+#[cfg(any(feature = "keyboard", feature = "mouse"))]
+use crate::Unknown;
+
+#[cfg(feature = "keyboard")]
+use crate::{Key, Led};
+
+#[cfg(feature = "mouse")]
+use crate::{Axis, Button};
+
+macro_rules! evdev_code {
+    ($($(#[$($meta:meta)*])* $type:ident;)*) => {
+        $(
+            $(#[$($meta)*])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            pub struct $type(pub u16);
+
+            impl From<u16> for $type {
+                fn from(code: u16) -> Self {
+                    Self(code)
+                }
+            }
+
+            impl From<$type> for u16 {
+                fn from(code: $type) -> Self {
+                    code.0
+                }
+            }
+        )*
+    };
+}
+
+evdev_code! {
+    /// Key or button code (`KEY_*` and `BTN_*`)
+    KeyCode;
+    /// LED code (`LED_*`)
+    LedCode;
+    /// Relative axis code (`REL_*`)
+    RelCode;
+}
+
+impl RelCode {
+    /// Horizontal wheel (`REL_HWHEEL`)
+    ///
+    /// It corresponds to Consumer AC Pan usage which is not [`Axis`](crate::Axis) of generic
+    /// desktop page, so it has no mapping.
+    pub const HWHEEL: Self = Self(0x06);
+}
+
+macro_rules! evdev_map {
+    ($($(#[$meta:meta])* $code_type:ident <=> $type:ident { $($code:literal <=> $var:ident,)* } $([ $($extra_var:ident => $extra_code:literal,)* ])?)*) => {
+        $(
+            $(#[$meta])*
+            impl TryFrom<$code_type> for $type {
+                type Error = Unknown;
+
+                fn try_from(code: $code_type) -> Result<Self, Unknown> {
+                    Ok(match code.0 {
+                        $($code => $type::$var,)*
+                        _ => return Err(Unknown),
+                    })
+                }
+            }
+
+            $(#[$meta])*
+            impl TryFrom<$type> for $code_type {
+                type Error = Unknown;
+
+                fn try_from(code: $type) -> Result<Self, Unknown> {
+                    #[allow(unreachable_patterns)]
+                    Ok($code_type(match code {
+                        $($type::$var => $code,)*
+                        $($($type::$extra_var => $extra_code,)*)?
+                        _ => return Err(Unknown),
+                    }))
+                }
+            }
+        )*
+    };
+}
+
+evdev_map! {
+    #[cfg(feature = "keyboard")]
+    KeyCode <=> Key {
+        1 <=> Esc,
+        2 <=> Num1,
+        3 <=> Num2,
+        4 <=> Num3,
+        5 <=> Num4,
+        6 <=> Num5,
+        7 <=> Num6,
+        8 <=> Num7,
+        9 <=> Num8,
+        10 <=> Num9,
+        11 <=> Num0,
+        12 <=> Minus,
+        13 <=> Equal,
+        14 <=> BackSpace,
+        15 <=> Tab,
+        16 <=> Q,
+        17 <=> W,
+        18 <=> E,
+        19 <=> R,
+        20 <=> T,
+        21 <=> Y,
+        22 <=> U,
+        23 <=> I,
+        24 <=> O,
+        25 <=> P,
+        26 <=> LeftBrace,
+        27 <=> RightBrace,
+        28 <=> Enter,
+        29 <=> LeftCtrl,
+        30 <=> A,
+        31 <=> S,
+        32 <=> D,
+        33 <=> F,
+        34 <=> G,
+        35 <=> H,
+        36 <=> J,
+        37 <=> K,
+        38 <=> L,
+        39 <=> Semicolon,
+        40 <=> Apostrophe,
+        41 <=> Grave,
+        42 <=> LeftShift,
+        43 <=> BackSlash,
+        44 <=> Z,
+        45 <=> X,
+        46 <=> C,
+        47 <=> V,
+        48 <=> B,
+        49 <=> N,
+        50 <=> M,
+        51 <=> Comma,
+        52 <=> Dot,
+        53 <=> Slash,
+        54 <=> RightShift,
+        55 <=> KeyPadAsterisk,
+        56 <=> LeftAlt,
+        57 <=> Space,
+        58 <=> CapsLock,
+        59 <=> F1,
+        60 <=> F2,
+        61 <=> F3,
+        62 <=> F4,
+        63 <=> F5,
+        64 <=> F6,
+        65 <=> F7,
+        66 <=> F8,
+        67 <=> F9,
+        68 <=> F10,
+        69 <=> NumLock,
+        70 <=> ScrollLock,
+        71 <=> KeyPad7,
+        72 <=> KeyPad8,
+        73 <=> KeyPad9,
+        74 <=> KeyPadMinus,
+        75 <=> KeyPad4,
+        76 <=> KeyPad5,
+        77 <=> KeyPad6,
+        78 <=> KeyPadPlus,
+        79 <=> KeyPad1,
+        80 <=> KeyPad2,
+        81 <=> KeyPad3,
+        82 <=> KeyPad0,
+        83 <=> KeyPadDot,
+        85 <=> ZankakuHankaku,
+        86 <=> NonUsBackSlash,
+        87 <=> F11,
+        88 <=> F12,
+        89 <=> Ro,
+        90 <=> Katakana,
+        91 <=> Hiragana,
+        92 <=> Henkan,
+        93 <=> KatakanaHiragana,
+        94 <=> Munenkan,
+        95 <=> KeyPadJpComma,
+        96 <=> KeyPadEnter,
+        97 <=> RightCtrl,
+        98 <=> KeyPadSlash,
+        99 <=> SysRq,
+        100 <=> RightAlt,
+        102 <=> Home,
+        103 <=> Up,
+        104 <=> PageUp,
+        105 <=> Left,
+        106 <=> Right,
+        107 <=> End,
+        108 <=> Down,
+        109 <=> PageDown,
+        110 <=> Insert,
+        111 <=> Delete,
+        113 <=> Mute,
+        114 <=> VolumeDown,
+        115 <=> VolumeUp,
+        116 <=> Power,
+        117 <=> KeyPadEqual,
+        119 <=> Pause,
+        121 <=> KeyPadComma,
+        122 <=> Hangeul,
+        123 <=> Hanja,
+        124 <=> Yen,
+        125 <=> LeftMeta,
+        126 <=> RightMeta,
+        127 <=> Compose,
+        128 <=> Stop,
+        129 <=> Again,
+        130 <=> Props,
+        131 <=> Undo,
+        132 <=> Front,
+        133 <=> Copy,
+        134 <=> Open,
+        135 <=> Paste,
+        136 <=> Find,
+        137 <=> Cut,
+        138 <=> Help,
+        179 <=> KeyPadLeftParen,
+        180 <=> KeyPadRightParen,
+        183 <=> F13,
+        184 <=> F14,
+        185 <=> F15,
+        186 <=> F16,
+        187 <=> F17,
+        188 <=> F18,
+        189 <=> F19,
+        190 <=> F20,
+        191 <=> F21,
+        192 <=> F22,
+        193 <=> F23,
+        194 <=> F24,
+    } [
+        // Linux uses the same code for both US and non-US hash keys
+        HashTilde => 43,
+    ]
+
+    #[cfg(feature = "keyboard")]
+    LedCode <=> Led {
+        0x00 <=> NumLock,
+        0x01 <=> CapsLock,
+        0x02 <=> ScrollLock,
+        0x03 <=> Compose,
+        0x04 <=> Kana,
+        0x05 <=> StandBy,
+        0x06 <=> SystemSuspend,
+        0x07 <=> Mute,
+        0x08 <=> GenericIndicator,
+        0x09 <=> MessageWaiting,
+        0x0a <=> ExternalPowerConnected,
+    }
+
+    #[cfg(feature = "mouse")]
+    KeyCode <=> Button {
+        0x110 <=> Primary,
+        0x111 <=> Secondary,
+        0x112 <=> Tertiary,
+        0x113 <=> Back,
+        0x114 <=> Forward,
+        0x115 <=> Sixth,
+        0x116 <=> Seventh,
+        0x117 <=> Eighth,
+    }
+
+    #[cfg(feature = "mouse")]
+    RelCode <=> Axis {
+        0x00 <=> X,
+        0x01 <=> Y,
+        0x08 <=> Wheel,
+    }
+}
+
+#[cfg(all(test, any(feature = "keyboard", feature = "mouse")))]
+mod test {
+    use super::*;
+
+    #[cfg(all(feature = "keyboard", feature = "display"))]
+    #[test]
+    fn key_codes() {
+        // usages which have no Linux key codes
+        let unmapped = [
+            Key::None,
+            Key::Overflow,
+            Key::PostFail,
+            Key::Undefined,
+            Key::LockingCapsLock,
+            Key::LockingNumLock,
+            Key::LockingScrollLock,
+            Key::KeyPadEqualSign,
+        ];
+
+        let mut mapped = 0;
+        for &key in Key::VARIANTS {
+            match KeyCode::try_from(key) {
+                Ok(code) => {
+                    assert!(!unmapped.contains(&key), "{key:?}");
+                    if key != Key::HashTilde {
+                        assert_eq!(Key::try_from(code), Ok(key));
+                    }
+                    mapped += 1;
+                }
+                Err(_) => assert!(unmapped.contains(&key), "{key:?}"),
+            }
+        }
+        assert_eq!(mapped, 148);
+
+        for code in 0..0x300 {
+            if let Ok(key) = Key::try_from(KeyCode(code)) {
+                assert_eq!(KeyCode::try_from(key), Ok(KeyCode(code)));
+            }
+        }
+
+        assert_eq!(Key::try_from(KeyCode(30)), Ok(Key::A));
+        assert_eq!(Key::try_from(KeyCode(125)), Ok(Key::LeftMeta));
+        assert_eq!(Key::try_from(KeyCode(0x110)), Err(Unknown));
+        assert_eq!(KeyCode::try_from(Key::HashTilde), Ok(KeyCode(43)));
+    }
+
+    #[cfg(feature = "keyboard")]
+    #[test]
+    fn led_codes() {
+        // whole LED_* table of input-event-codes.h
+        for (code, led) in [
+            (0x00, Led::NumLock),                // LED_NUML
+            (0x01, Led::CapsLock),               // LED_CAPSL
+            (0x02, Led::ScrollLock),             // LED_SCROLLL
+            (0x03, Led::Compose),                // LED_COMPOSE
+            (0x04, Led::Kana),                   // LED_KANA
+            (0x05, Led::StandBy),                // LED_SLEEP
+            (0x06, Led::SystemSuspend),          // LED_SUSPEND
+            (0x07, Led::Mute),                   // LED_MUTE
+            (0x08, Led::GenericIndicator),       // LED_MISC
+            (0x09, Led::MessageWaiting),         // LED_MAIL
+            (0x0a, Led::ExternalPowerConnected), // LED_CHARGING
+        ] {
+            assert_eq!(LedCode::try_from(led), Ok(LedCode(code)));
+            assert_eq!(Led::try_from(LedCode(code)), Ok(led));
+        }
+        assert_eq!(LedCode::try_from(Led::None), Err(Unknown));
+        assert_eq!(Led::try_from(LedCode(0x0b)), Err(Unknown));
+    }
+
+    #[cfg(feature = "mouse")]
+    #[test]
+    fn button_codes() {
+        for raw in 0x01..=0x08 {
+            let button = Button::safe_from(raw).unwrap();
+            let code = KeyCode::try_from(button).unwrap();
+            assert_eq!(code, KeyCode(0x10f + raw as u16));
+            assert_eq!(Button::try_from(code), Ok(button));
+        }
+        assert_eq!(KeyCode::try_from(Button::None), Err(Unknown));
+        assert_eq!(Button::try_from(KeyCode(30)), Err(Unknown));
+        assert_eq!(Button::try_from(KeyCode(0x118)), Err(Unknown));
+    }
+
+    #[cfg(feature = "mouse")]
+    #[test]
+    fn rel_codes() {
+        for (axis, code) in [(Axis::X, 0x00), (Axis::Y, 0x01), (Axis::Wheel, 0x08)] {
+            assert_eq!(RelCode::try_from(axis), Ok(RelCode(code)));
+            assert_eq!(Axis::try_from(RelCode(code)), Ok(axis));
+        }
+        assert_eq!(Axis::try_from(RelCode::HWHEEL), Err(Unknown));
+        assert_eq!(Axis::try_from(RelCode(0x07)), Err(Unknown));
+    }
+}
@@ -5,7 +5,7 @@ use static_assertions::const_assert_eq;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{Class, StateChange};
+use crate::{Boot, Class, EmptyReport, RawReport, StateChange};
 
 /// Keyboard HID class
 #[derive(Clone, Copy, Debug)]
@@ -14,6 +14,7 @@ pub struct Keyboard;
 impl Class for Keyboard {
     type Input = KeyboardInput;
     type Output = KeyboardOutput;
+    type Feature = EmptyReport;
 
     fn input(&self) -> Self::Input {
         Self::Input::default()
@@ -22,6 +23,19 @@ impl Class for Keyboard {
     fn output(&self) -> Self::Output {
         Self::Output::default()
     }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl Boot for Keyboard {
+    /// Keyboard input report already has boot protocol layout
+    type BootInput = KeyboardInput;
+
+    fn boot_input(input: &Self::Input) -> Self::BootInput {
+        *input
+    }
 }
 
 impl AsRef<str> for Keyboard {
@@ -82,8 +96,10 @@ impl From<Modifiers> for u8 {
 
 code_enum! {
     /// Key code
+    #[derive(Default)]
     Key: u8 {
         /// No key
+        #[default]
         None = 0x00 => "none",
 
         /// Keyboard Error Roll Over - used for all slots if too many keys are pressed ("Phantom key")
@@ -505,12 +521,6 @@ impl Key {
     }
 }
 
-impl Default for Key {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 code_enum! {
     /// LED code
     Led: u8 {
@@ -526,13 +536,160 @@ code_enum! {
         Compose = 0x04 => "compose",
         /// Kana LED
         Kana = 0x05 => "kana",
+        /// Power
+        Power = 0x06 => "power",
+        /// Shift
+        Shift = 0x07 => "shift",
+        /// Do not disturb
+        DoNotDisturb = 0x08 => "do-not-disturb" | "dnd",
+        /// Mute
+        Mute = 0x09 => "mute",
+        /// Tone enable
+        ToneEnable = 0x0a => "tone-enable",
+        /// High cut filter
+        HighCutFilter = 0x0b => "high-cut-filter",
+        /// Low cut filter
+        LowCutFilter = 0x0c => "low-cut-filter",
+        /// Equalizer enable
+        EqualizerEnable = 0x0d => "equalizer-enable",
+        /// Sound field on
+        SoundFieldOn = 0x0e => "sound-field-on",
+        /// Surround on
+        SurroundOn = 0x0f => "surround-on",
+        /// Repeat
+        Repeat = 0x10 => "repeat",
+        /// Stereo
+        Stereo = 0x11 => "stereo",
+        /// Sampling rate detect
+        SamplingRateDetect = 0x12 => "sampling-rate-detect",
+        /// Spinning
+        Spinning = 0x13 => "spinning",
+        /// Constant angular velocity
+        Cav = 0x14 => "cav",
+        /// Constant linear velocity
+        Clv = 0x15 => "clv",
+        /// Recording format detect
+        RecordingFormatDetect = 0x16 => "recording-format-detect",
+        /// Off-hook
+        OffHook = 0x17 => "off-hook",
+        /// Ring
+        Ring = 0x18 => "ring",
+        /// Message waiting
+        MessageWaiting = 0x19 => "message-waiting" | "mail",
+        /// Data mode
+        DataMode = 0x1a => "data-mode",
+        /// Battery operation
+        BatteryOperation = 0x1b => "battery-operation",
+        /// Battery OK
+        BatteryOk = 0x1c => "battery-ok",
+        /// Battery low
+        BatteryLow = 0x1d => "battery-low",
+        /// Speaker
+        Speaker = 0x1e => "speaker",
+        /// Head set
+        HeadSet = 0x1f => "head-set" | "headset",
+        /// Hold
+        Hold = 0x20 => "hold",
+        /// Microphone
+        Microphone = 0x21 => "microphone",
+        /// Coverage
+        Coverage = 0x22 => "coverage",
+        /// Night mode
+        NightMode = 0x23 => "night-mode",
+        /// Send calls
+        SendCalls = 0x24 => "send-calls",
+        /// Call pickup
+        CallPickup = 0x25 => "call-pickup",
+        /// Conference
+        Conference = 0x26 => "conference",
+        /// Stand-by
+        StandBy = 0x27 => "stand-by" | "standby" | "sleep",
+        /// Camera on
+        CameraOn = 0x28 => "camera-on",
+        /// Camera off
+        CameraOff = 0x29 => "camera-off",
+        /// On-line
+        OnLine = 0x2a => "on-line" | "online",
+        /// Off-line
+        OffLine = 0x2b => "off-line" | "offline",
+        /// Busy
+        Busy = 0x2c => "busy",
+        /// Ready
+        Ready = 0x2d => "ready",
+        /// Paper out
+        PaperOut = 0x2e => "paper-out",
+        /// Paper jam
+        PaperJam = 0x2f => "paper-jam",
+        /// Remote
+        Remote = 0x30 => "remote",
+        /// Forward
+        Forward = 0x31 => "forward",
+        /// Reverse
+        Reverse = 0x32 => "reverse",
+        /// Stop
+        Stop = 0x33 => "stop",
+        /// Rewind
+        Rewind = 0x34 => "rewind",
+        /// Fast forward
+        FastForward = 0x35 => "fast-forward",
+        /// Play
+        Play = 0x36 => "play",
+        /// Pause
+        Pause = 0x37 => "pause",
+        /// Record
+        Record = 0x38 => "record",
+        /// Error
+        Error = 0x39 => "error",
+        /// Usage selected indicator
+        UsageSelectedIndicator = 0x3a => "usage-selected-indicator",
+        /// Usage in use indicator
+        UsageInUseIndicator = 0x3b => "usage-in-use-indicator",
+        /// Usage multi mode indicator
+        UsageMultiModeIndicator = 0x3c => "usage-multi-mode-indicator",
+        /// Indicator on
+        IndicatorOn = 0x3d => "indicator-on",
+        /// Indicator flash
+        IndicatorFlash = 0x3e => "indicator-flash",
+        /// Indicator slow blink
+        IndicatorSlowBlink = 0x3f => "indicator-slow-blink",
+        /// Indicator fast blink
+        IndicatorFastBlink = 0x40 => "indicator-fast-blink",
+        /// Indicator off
+        IndicatorOff = 0x41 => "indicator-off",
+        /// Flash on time
+        FlashOnTime = 0x42 => "flash-on-time",
+        /// Slow blink on time
+        SlowBlinkOnTime = 0x43 => "slow-blink-on-time",
+        /// Slow blink off time
+        SlowBlinkOffTime = 0x44 => "slow-blink-off-time",
+        /// Fast blink on time
+        FastBlinkOnTime = 0x45 => "fast-blink-on-time",
+        /// Fast blink off time
+        FastBlinkOffTime = 0x46 => "fast-blink-off-time",
+        /// Usage indicator color
+        UsageIndicatorColor = 0x47 => "usage-indicator-color",
+        /// Indicator red
+        IndicatorRed = 0x48 => "indicator-red",
+        /// Indicator green
+        IndicatorGreen = 0x49 => "indicator-green",
+        /// Indicator amber
+        IndicatorAmber = 0x4a => "indicator-amber",
+        /// Generic indicator
+        GenericIndicator = 0x4b => "generic-indicator" | "misc",
+        /// System suspend
+        SystemSuspend = 0x4c => "system-suspend" | "suspend",
+        /// External power connected
+        ExternalPowerConnected = 0x4d => "external-power-connected" | "charging",
     }
 }
 
 impl Led {
+    /// Last LED code of LED usage page
+    pub const MAX: u8 = 0x4d;
+
     /// Converts from raw value safely
     pub fn safe_from(raw: u8) -> Option<Self> {
-        if (0x01..=0x05).contains(&raw) {
+        if (0x01..=Self::MAX).contains(&raw) {
             Some(From::from(raw))
         } else {
             None
@@ -554,6 +711,12 @@ bitflags! {
         const Compose = 0x08;
         /// Kana LED
         const Kana = 0x10;
+        /// Power LED
+        const Power = 0x20;
+        /// Shift LED
+        const Shift = 0x40;
+        /// Do not disturb LED
+        const DoNotDisturb = 0x80;
     }
 }
 
@@ -568,11 +731,7 @@ impl Default for Leds {
 impl Leds {
     /// Convert from raw value safely
     pub fn safe_from(raw: u8) -> Option<Self> {
-        if raw & 0xe0 == 0 {
-            Self::from_bits(raw)
-        } else {
-            None
-        }
+        Self::from_bits(raw)
     }
 }
 
@@ -583,12 +742,12 @@ impl From<Leds> for u8 {
 }
 
 impl From<Led> for Leds {
+    /// LEDs which does not fit into mask is converted to empty mask
     fn from(code: Led) -> Self {
-        Self::from_bits_retain(if matches!(code, Led::None) {
-            0u8
-        } else {
-            1u8 << (code as u8 - Led::NumLock as u8)
-        })
+        match code as u8 {
+            code @ 0x01..=0x08 => Self::from_bits_retain(1u8 << (code - Led::NumLock as u8)),
+            _ => Self::empty(),
+        }
     }
 }
 
@@ -916,12 +1075,14 @@ impl KeyboardOutput {
         self.leds.bits().count_ones() as _
     }
 
+    /// Check that LED is lit
+    pub fn is_lit(&self, led: Led) -> bool {
+        is_lit(self.as_ref(), led)
+    }
+
     /// Get iterator over lit LEDs
-    pub fn lit(&self) -> LitLeds {
-        LitLeds {
-            report: self,
-            element: 0,
-        }
+    pub fn lit(&self) -> LitLeds<'_> {
+        LitLeds::new(self.as_ref())
     }
 
     /// Change LEDs state
@@ -944,6 +1105,9 @@ impl KeyboardOutput {
     }
 
     /// Change LED state
+    ///
+    /// LEDs after [`Led::DoNotDisturb`] is ignored because report has no room for them,
+    /// use [`ExtendedKeyboardOutput`] instead.
     pub fn change_led(&mut self, led: Led, state: bool) {
         self.change_leds(led.into(), state);
     }
@@ -962,11 +1126,7 @@ impl KeyboardOutput {
     ///
     /// Difference of two reports
     pub fn diff<'i>(&'i self, other: &'i Self) -> LedStateChanges<'i> {
-        LedStateChanges {
-            new: self,
-            old: other,
-            element: 0,
-        }
+        LedStateChanges::new(self.as_ref(), other.as_ref())
     }
 }
 
@@ -980,32 +1140,54 @@ impl<'i> core::ops::Sub<&'i KeyboardOutput> for &'i KeyboardOutput {
 
 /// Changes between keyboard output reports
 pub struct LedStateChanges<'i> {
-    new: &'i KeyboardOutput,
-    old: &'i KeyboardOutput,
+    new: &'i [u8],
+    old: &'i [u8],
     element: u8,
 }
 
+impl<'i> LedStateChanges<'i> {
+    fn new(new: &'i [u8], old: &'i [u8]) -> Self {
+        Self {
+            new,
+            old,
+            element: 0,
+        }
+    }
+}
+
 impl<'i> Iterator for LedStateChanges<'i> {
     type Item = StateChange<Led>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if self.element < 8 {
-                // find changed leds
-                let leds = Leds::from_bits_retain(1 << self.element);
-                self.element += 1;
-                if Leds::empty() != ((self.new.leds ^ self.old.leds) & leds) {
-                    let led = Led::from(leds);
-                    let old_led = Led::from(self.old.leds & leds);
-                    return Some(StateChange::new(led, matches!(old_led, Led::None)));
-                }
-            } else {
-                return None;
+        // find changed leds
+        while let Some(led) = next_led(&mut self.element, self.new.len()) {
+            let (new, old) = (is_lit(self.new, led), is_lit(self.old, led));
+            if new != old {
+                return Some(StateChange::new(led, new));
             }
         }
+        None
     }
 }
 
+/// Get LED of next bit of LEDs bitmap
+fn next_led(element: &mut u8, len: usize) -> Option<Led> {
+    if *element >= Led::MAX || *element as usize >= len * 8 {
+        return None;
+    }
+    *element += 1;
+    Some(Led::from(*element))
+}
+
+/// Check state of LED in LEDs bitmap
+fn is_lit(leds: &[u8], led: Led) -> bool {
+    let Some(index) = (led as usize).checked_sub(1) else {
+        return false;
+    };
+    leds.get(index / 8)
+        .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
+
 impl Extend<StateChange<Leds>> for KeyboardOutput {
     fn extend<T>(&mut self, iter: T)
     where
@@ -1041,25 +1223,253 @@ impl Extend<KeyboardOutput> for KeyboardOutput {
 
 /// An iterator over lit LEDs
 pub struct LitLeds<'i> {
-    report: &'i KeyboardOutput,
+    leds: &'i [u8],
     element: u8,
 }
 
+impl<'i> LitLeds<'i> {
+    fn new(leds: &'i [u8]) -> Self {
+        Self { leds, element: 0 }
+    }
+}
+
 impl<'i> Iterator for LitLeds<'i> {
     type Item = Led;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.element < 8 {
-            let led = Leds::from_bits_retain(1u8 << self.element);
-            self.element += 1;
-            if self.report.leds.contains(led) {
-                return Some(led.into());
+        while let Some(led) = next_led(&mut self.element, self.leds.len()) {
+            if is_lit(self.leds, led) {
+                return Some(led);
             }
         }
         None
     }
 }
 
+/// Keyboard HID class with extended LEDs output report
+///
+/// Output report has `N` bytes of LEDs bitmap where each bit corresponds to one LED usage
+/// starting from [`Led::NumLock`], so descriptor should declare `N * 8` LEDs.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtendedKeyboard<const N: usize>;
+
+impl<const N: usize> Class for ExtendedKeyboard<N> {
+    type Input = KeyboardInput;
+    type Output = ExtendedKeyboardOutput<N>;
+    type Feature = EmptyReport;
+
+    fn input(&self) -> Self::Input {
+        Self::Input::default()
+    }
+
+    fn output(&self) -> Self::Output {
+        Self::Output::default()
+    }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl<const N: usize> Boot for ExtendedKeyboard<N> {
+    type BootInput = KeyboardInput;
+
+    fn boot_input(input: &Self::Input) -> Self::BootInput {
+        *input
+    }
+}
+
+impl<const N: usize> AsRef<str> for ExtendedKeyboard<N> {
+    fn as_ref(&self) -> &str {
+        "extended-keyboard"
+    }
+}
+
+impl<const N: usize> core::fmt::Display for ExtendedKeyboard<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// Keyboard output report with extended LEDs bitmap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ExtendedKeyboardOutput<const N: usize> {
+    /// Active LEDs bitmap
+    leds: [u8; N],
+}
+
+impl<const N: usize> Default for ExtendedKeyboardOutput<N> {
+    fn default() -> Self {
+        Self { leds: [0; N] }
+    }
+}
+
+impl<const N: usize> ExtendedKeyboardOutput<N> {
+    /// Get mask of first eight LEDs
+    pub fn leds(&self) -> Leds {
+        Leds::from_bits_retain(self.leds.first().copied().unwrap_or_default())
+    }
+
+    /// Get number of lit LEDs
+    pub fn count_lit(&self) -> usize {
+        self.lit().count()
+    }
+
+    /// Check that LED is lit
+    pub fn is_lit(&self, led: Led) -> bool {
+        is_lit(&self.leds, led)
+    }
+
+    /// Get iterator over lit LEDs
+    pub fn lit(&self) -> LitLeds<'_> {
+        LitLeds::new(&self.leds)
+    }
+
+    /// Change state of first eight LEDs
+    pub fn change_leds(&mut self, leds: Leds, state: bool) {
+        if let Some(byte) = self.leds.first_mut() {
+            if state {
+                *byte |= leds.bits();
+            } else {
+                *byte &= !leds.bits();
+            }
+        }
+    }
+
+    /// Turn LEDs on
+    pub fn on_leds(&mut self, leds: Leds) {
+        self.change_leds(leds, true);
+    }
+
+    /// Turn LEDs off
+    pub fn off_leds(&mut self, leds: Leds) {
+        self.change_leds(leds, false);
+    }
+
+    /// Change LED state
+    ///
+    /// LEDs which does not fit into bitmap is ignored.
+    pub fn change_led(&mut self, led: Led, state: bool) {
+        let Some(index) = (led as usize).checked_sub(1) else {
+            return;
+        };
+        if let Some(byte) = self.leds.get_mut(index / 8) {
+            let bit = 1 << (index % 8);
+            if state {
+                *byte |= bit;
+            } else {
+                *byte &= !bit;
+            }
+        }
+    }
+
+    /// Turn LED on
+    pub fn on_led(&mut self, led: Led) {
+        self.change_led(led, true);
+    }
+
+    /// Turn LED off
+    pub fn off_led(&mut self, led: Led) {
+        self.change_led(led, false);
+    }
+
+    /// Get LED state changes between two reports
+    ///
+    /// Difference of two reports
+    pub fn diff<'i>(&'i self, other: &'i Self) -> LedStateChanges<'i> {
+        LedStateChanges::new(&self.leds, &other.leds)
+    }
+}
+
+impl<'i, const N: usize> core::ops::Sub<&'i ExtendedKeyboardOutput<N>>
+    for &'i ExtendedKeyboardOutput<N>
+{
+    type Output = LedStateChanges<'i>;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.diff(other)
+    }
+}
+
+impl<const N: usize> From<KeyboardOutput> for ExtendedKeyboardOutput<N> {
+    fn from(output: KeyboardOutput) -> Self {
+        let mut extended = Self::default();
+        extended.on_leds(output.leds);
+        extended
+    }
+}
+
+impl<const N: usize> Extend<StateChange<Leds>> for ExtendedKeyboardOutput<N> {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = StateChange<Leds>>,
+    {
+        for StateChange { data, state } in iter {
+            self.change_leds(data, state);
+        }
+    }
+}
+
+impl<const N: usize> Extend<StateChange<Led>> for ExtendedKeyboardOutput<N> {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = StateChange<Led>>,
+    {
+        for StateChange { data, state } in iter {
+            self.change_led(data, state);
+        }
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ExtendedKeyboardOutput<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.leds
+    }
+}
+
+/// LEDs bitmap is serialized as byte sequence under the same `led` key as in [`KeyboardOutput`]
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct ExtendedKeyboardOutputSerde<L> {
+    led: L,
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> Serialize for ExtendedKeyboardOutput<N> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        ExtendedKeyboardOutputSerde {
+            led: &self.leds[..],
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> Deserialize<'de> for ExtendedKeyboardOutput<N> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = ExtendedKeyboardOutputSerde::<Vec<u8>>::deserialize(deserializer)?;
+        let leds = raw.led.try_into().map_err(|leds: Vec<u8>| {
+            serde::de::Error::invalid_length(leds.len(), &"LEDs bitmap of report size")
+        })?;
+        Ok(Self { leds })
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for ExtendedKeyboardOutput<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.leds
+    }
+}
+
+unsafe impl<const N: usize> RawReport for ExtendedKeyboardOutput<N> {}
+
 raw_ref! {
     Modifiers;
     Leds;
@@ -1069,6 +1479,17 @@ raw_ref! {
     KeyboardOutput;
 }
 
+// key codes is sparse so only masks accept arbitrary bytes
+raw_mut! {
+    Modifiers;
+    Leds;
+    KeyboardOutput;
+}
+
+unsafe impl RawReport for Modifiers {}
+unsafe impl RawReport for Leds {}
+unsafe impl RawReport for KeyboardOutput {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1227,4 +1648,56 @@ mod test {
         );
         assert_eq!(changes.next(), None);
     }
+
+    #[test]
+    fn led_codes() {
+        assert_eq!(Led::safe_from(Led::MAX), Some(Led::ExternalPowerConnected));
+        assert_eq!(Led::safe_from(Led::MAX + 1), None);
+        assert_eq!(Leds::safe_from(0xff), Some(Leds::all()));
+        assert_eq!(Leds::from(Led::DoNotDisturb), Leds::DoNotDisturb);
+        assert_eq!(Led::from(Leds::Shift), Led::Shift);
+        assert_eq!(Leds::from(Led::Mute), Leds::empty());
+
+        let mut report = KeyboardOutput::default();
+        report.on_led(Led::DoNotDisturb);
+        report.on_led(Led::Mute);
+        assert!(report.is_lit(Led::DoNotDisturb));
+        assert_eq!(report.lit().collect::<Vec<_>>(), [Led::DoNotDisturb]);
+    }
+
+    #[test]
+    fn extended_keyboard_output() {
+        let mut old = ExtendedKeyboard::<10>.output();
+        old.on_leds(Leds::CapsLock);
+        old.on_led(Led::Mute);
+
+        let mut base = KeyboardOutput::default();
+        base.on_led(Led::Shift);
+        let mut new = ExtendedKeyboardOutput::<10>::from(base);
+        new.on_led(Led::Mute);
+        new.on_led(Led::ExternalPowerConnected);
+        assert_eq!(new.as_ref()[..2], [0x40, 0x01]);
+        assert_eq!(new.as_ref()[9], 0x10);
+        assert_eq!(new.count_lit(), 3);
+        assert_eq!(
+            new.lit().collect::<Vec<_>>(),
+            [Led::Shift, Led::Mute, Led::ExternalPowerConnected]
+        );
+
+        let changes: Vec<_> = (&new - &old).collect();
+        assert_eq!(
+            changes,
+            [
+                StateChange::new(Led::CapsLock, false),
+                StateChange::new(Led::Shift, true),
+                StateChange::new(Led::ExternalPowerConnected, true),
+            ]
+        );
+
+        // LEDs which does not fit into report is ignored
+        let mut short = ExtendedKeyboardOutput::<1>::default();
+        short.on_led(Led::Mute);
+        short.extend([StateChange::on(Led::NumLock)]);
+        assert_eq!(short.as_ref(), [0x01]);
+    }
 }
@@ -11,32 +11,82 @@ use serde::{Deserialize, Serialize};
 #[macro_use]
 mod macros;
 
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+mod composite;
+
+mod connection;
+mod error;
+mod pace;
+mod report_id;
+
+#[cfg(feature = "ctaphid")]
+pub mod ctaphid;
+
 #[cfg(feature = "either")]
 mod either_report;
 
+#[cfg(feature = "evdev")]
+pub mod evdev;
+
 #[cfg(feature = "keyboard")]
 mod keyboard;
 
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "stream")]
+pub mod stream;
+
 #[cfg(feature = "mouse")]
 mod mouse;
 
+#[cfg(feature = "power")]
+mod power;
+
+#[cfg(feature = "raw-hid")]
+mod raw_hid;
+
+#[cfg(feature = "telephony")]
+mod telephony;
+
 #[cfg(feature = "keyboard")]
 pub use keyboard::{
-    Key, KeyStateChanges, Keyboard, KeyboardInput, KeyboardOutput, Led, LedStateChanges, Leds,
-    Modifiers,
+    ExtendedKeyboard, ExtendedKeyboardOutput, Key, KeyStateChanges, Keyboard, KeyboardInput,
+    KeyboardOutput, Led, LedStateChanges, Leds, Modifiers,
 };
 
 #[cfg(feature = "mouse")]
 pub use mouse::{
-    Button, Buttons, Mouse, MouseInput, MouseInputChange, MouseInputChanges, MouseOutput,
+    Axis, BootMouseInput, Button, Buttons, GenericMouseInput, GenericMouseInputChange,
+    GenericMouseInputChanges, HiResMouse, HiResMouseInput, Mouse, MouseInput, MouseInputChange,
+    MouseInputChanges, MouseMotion, MouseOutput, PanMouse, PanMouseInput, ResolutionMultiplier,
+    Scroller, WheelValue,
 };
 
-use std::{
-    io::ErrorKind,
-    path::{Path, PathBuf},
+#[cfg(feature = "power")]
+pub use power::{PowerDevice, PowerFlags, PowerStatus};
+
+#[cfg(feature = "raw-hid")]
+pub use raw_hid::RawHid;
+
+#[cfg(feature = "telephony")]
+pub use telephony::{
+    LitPhoneLeds, PhoneButton, PhoneButtonChanges, PhoneButtons, PhoneLed, PhoneLedChanges,
+    PhoneLeds, PressedPhoneButtons, Telephony, TelephonyInput, TelephonyOutput,
+};
+
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+pub use composite::{
+    CompositeEvent, CompositeInput, CompositeRead, CompositeState, CompositeTarget, CompositeWrite,
 };
 
-pub use std::io::{Error, Result};
+pub use connection::{ConnectionState, ConnectionWatch, ConnectionWatcher, Reconnect};
+
+use std::path::{Path, PathBuf};
+
+pub use error::{Error, Result};
+pub use pace::{Coalesce, Pacer};
+pub use report_id::{Demux, IdReport, Identified};
 
 /// Unknown error
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -199,13 +249,71 @@ pub trait Class {
     /// Output report type
     type Output;
 
+    /// Feature report type
+    ///
+    /// Use [`EmptyReport`] when class has no feature reports.
+    type Feature;
+
     /// Create input report
     fn input(&self) -> Self::Input;
 
     /// Create output report
     fn output(&self) -> Self::Output;
+
+    /// Create feature report
+    fn feature(&self) -> Self::Feature;
+}
+
+/// Protocol of boot subclass device
+///
+/// Host selects protocol using SET_PROTOCOL request. Firmware usually selects boot protocol
+/// and operating systems selects report protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Protocol {
+    /// Boot protocol with fixed report layout
+    Boot,
+    /// Report protocol with layout declared by report descriptor
+    #[default]
+    Report,
+}
+
+/// Class which supports boot protocol
+pub trait Boot: Class {
+    /// Input report type of boot protocol
+    type BootInput: AsRef<[u8]>;
+
+    /// Convert input report to boot protocol layout
+    fn boot_input(input: &Self::Input) -> Self::BootInput;
+}
+
+/// Report which is plain bytes
+///
+/// # Safety
+///
+/// Type must be byte aligned and its [`AsRef`] and [`AsMut`] implementations must cover
+/// whole value. Every bit pattern must be a valid value because received bytes is written
+/// into report as is.
+pub unsafe trait RawReport: AsRef<[u8]> + AsMut<[u8]> {}
+
+unsafe impl<const N: usize> RawReport for [u8; N] {}
+
+/// Report without data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct EmptyReport {}
+
+raw_ref! {
+    EmptyReport;
 }
 
+raw_mut! {
+    EmptyReport;
+}
+
+unsafe impl RawReport for EmptyReport {}
+
 /// Device path trait
 pub trait AsDevicePath {
     /// Get absolute device path
@@ -304,12 +412,34 @@ impl<T> core::ops::DerefMut for Internal<T> {
     }
 }
 
+/// Check error of report transfer
+///
+/// Turns OS errors which is specific to HID gadget devices into corresponding [`Error`] variants.
+pub fn check_error(error: std::io::Error) -> Error {
+    error.into()
+}
+
+/// Check error of input report sending
+///
+/// Same as [`check_error`] but also turns `EAGAIN` into [`Error::Busy`] because on write
+/// it means that host does not poll input endpoint.
+pub fn check_write_error(error: std::io::Error) -> Error {
+    if error.kind() == std::io::ErrorKind::WouldBlock {
+        Error::Busy
+    } else {
+        error.into()
+    }
+}
+
 /// Check write report length
 pub fn check_write(actual: usize, expected: usize) -> Result<()> {
     if actual == expected {
         Ok(())
     } else {
-        Err(Error::new(ErrorKind::Other, "Error when writing report"))
+        Err(Error::ShortWrite {
+            written: actual,
+            expected,
+        })
     }
 }
 
@@ -318,6 +448,9 @@ pub fn check_read(actual: usize, expected: usize) -> Result<()> {
     if actual == expected {
         Ok(())
     } else {
-        Err(Error::new(ErrorKind::Other, "Error when reading report"))
+        Err(Error::ShortRead {
+            read: actual,
+            expected,
+        })
     }
 }
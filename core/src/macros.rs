@@ -1,6 +1,18 @@
 #[allow(unused_macros)]
 macro_rules! code_enum {
-    ($($(#[$($type_meta:meta)*])* $type:ident: $repr_type:ty { $($(#[$($var_meta:meta)*])* $var:ident = $val:literal => $str:literal $(| $strs:literal)*,)* })*) => {
+    // sparse enums declares fallback variant for unassigned codes
+    (@from $type:ident, $raw:ident { $($var:ident = $val:literal,)* }) => {
+        unsafe { transmute($raw) }
+    };
+
+    (@from $type:ident, $raw:ident, $fallback:ident { $($var:ident = $val:literal,)* }) => {
+        match $raw {
+            $($val => $type::$var,)*
+            _ => $type::$fallback,
+        }
+    };
+
+    ($($(#[$($type_meta:meta)*])* $type:ident: $repr_type:ty $(= $fallback:ident)? { $($(#[$($var_meta:meta)*])* $var:ident = $val:literal => $str:literal $(| $strs:literal)*,)* })*) => {
         $(
             $(#[$($type_meta)*])*
             #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -13,7 +25,7 @@ macro_rules! code_enum {
 
             impl From<u8> for $type {
                 fn from(raw: u8) -> Self {
-                    unsafe { transmute(raw) }
+                    code_enum!(@from $type, raw $(, $fallback)? { $($var = $val,)* })
                 }
             }
 
@@ -188,6 +200,16 @@ macro_rules! raw_ref {
                 }
             }
 
+            static_assertions::const_assert_eq!(core::mem::align_of::<$type>(), 1);
+        )*
+    };
+}
+
+/// Mutable bytes access which is sound only when any bit pattern is valid value
+#[allow(unused_macros)]
+macro_rules! raw_mut {
+    ($($type:ty;)*) => {
+        $(
             impl AsMut<[u8]> for $type {
                 fn as_mut(&mut self) -> &mut [u8] {
                     unsafe {
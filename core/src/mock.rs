@@ -0,0 +1,259 @@
+//! In-memory transport for testing
+//!
+//! [`MockTransport`] behaves like `/dev/hidgX` opened in non-blocking mode: each write records
+//! one input report and each read returns one output report previously injected by test.
+//! Clones share the same state so test can keep one clone while device owns another.
+
+use std::{
+    collections::VecDeque,
+    io::{Error, Read, Result, Write},
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+/// Simulated transport failure
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Operation would block (`EAGAIN`)
+    Again,
+    /// Host is not connected (`ESHUTDOWN`)
+    Shutdown,
+    /// Only given number of bytes is transferred
+    Short(usize),
+    /// Arbitrary OS error
+    Os(i32),
+}
+
+impl Fault {
+    fn error(self) -> Option<Error> {
+        match self {
+            Fault::Again => Some(Error::from_raw_os_error(libc::EAGAIN)),
+            Fault::Shutdown => Some(Error::from_raw_os_error(libc::ESHUTDOWN)),
+            Fault::Short(_) => None,
+            Fault::Os(code) => Some(Error::from_raw_os_error(code)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    inputs: Vec<Vec<u8>>,
+    outputs: VecDeque<Vec<u8>>,
+    write_faults: VecDeque<Fault>,
+    read_faults: VecDeque<Fault>,
+    reader: Option<Waker>,
+    closed: bool,
+}
+
+/// In-memory transport
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTransport {
+    /// Create new mock transport
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Get input reports written so far
+    pub fn inputs(&self) -> Vec<Vec<u8>> {
+        self.state().inputs.clone()
+    }
+
+    /// Take input reports written so far
+    pub fn take_inputs(&self) -> Vec<Vec<u8>> {
+        core::mem::take(&mut self.state().inputs)
+    }
+
+    /// Inject output report which will be received by device
+    pub fn push_output(&self, report: impl AsRef<[u8]>) {
+        let mut state = self.state();
+        state.outputs.push_back(report.as_ref().to_vec());
+        if let Some(waker) = state.reader.take() {
+            waker.wake();
+        }
+    }
+
+    /// Make next write fail
+    ///
+    /// Faults is applied in order of addition, one per write.
+    pub fn fail_write(&self, fault: Fault) {
+        self.state().write_faults.push_back(fault);
+    }
+
+    /// Make next read fail
+    ///
+    /// Faults is applied in order of addition, one per read.
+    pub fn fail_read(&self, fault: Fault) {
+        let mut state = self.state();
+        state.read_faults.push_back(fault);
+        if let Some(waker) = state.reader.take() {
+            waker.wake();
+        }
+    }
+
+    /// Simulate end of file
+    ///
+    /// Reads return 0 bytes once injected output reports is received.
+    pub fn close(&self) {
+        let mut state = self.state();
+        state.closed = true;
+        if let Some(waker) = state.reader.take() {
+            waker.wake();
+        }
+    }
+
+    /// Write input report
+    pub fn write_report(&self, report: &[u8]) -> Result<usize> {
+        let mut state = self.state();
+        let len = match state.write_faults.pop_front() {
+            Some(Fault::Short(len)) => len.min(report.len()),
+            Some(fault) => return Err(fault.error().unwrap()),
+            None => report.len(),
+        };
+        state.inputs.push(report[..len].to_vec());
+        Ok(len)
+    }
+
+    /// Read output report
+    ///
+    /// Fails with `WouldBlock` when there is no output reports.
+    pub fn read_report(&self, report: &mut [u8]) -> Result<usize> {
+        match self.read(None, report) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(std::io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    /// Poll for output report
+    pub fn poll_read_report(&self, cx: &mut Context<'_>, report: &mut [u8]) -> Poll<Result<usize>> {
+        self.read(Some(cx), report)
+    }
+
+    fn read(&self, cx: Option<&mut Context<'_>>, report: &mut [u8]) -> Poll<Result<usize>> {
+        let mut state = self.state();
+        if let Some(error) = state.read_faults.front().and_then(|fault| fault.error()) {
+            state.read_faults.pop_front();
+            return Poll::Ready(Err(error));
+        }
+        let Some(output) = state.outputs.pop_front() else {
+            if state.closed {
+                return Poll::Ready(Ok(0));
+            }
+            if let Some(cx) = cx {
+                state.reader = Some(cx.waker().clone());
+            }
+            return Poll::Pending;
+        };
+        let limit = match state.read_faults.pop_front() {
+            Some(Fault::Short(len)) => len,
+            _ => report.len(),
+        };
+        let len = output.len().min(report.len()).min(limit);
+        report[..len].copy_from_slice(&output[..len]);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl Read for MockTransport {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.read_report(buf)
+    }
+}
+
+impl Write for MockTransport {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.write_report(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for &MockTransport {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.read_report(buf)
+    }
+}
+
+impl Write for &MockTransport {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.write_report(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn records_inputs() {
+        let mock = MockTransport::new();
+        let device = mock.clone();
+
+        assert_eq!(device.write_report(&[1, 2, 3]).unwrap(), 3);
+        assert_eq!(device.write_report(&[4, 5]).unwrap(), 2);
+
+        assert_eq!(mock.inputs(), [vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(mock.take_inputs().len(), 2);
+        assert!(mock.inputs().is_empty());
+    }
+
+    #[test]
+    fn injects_outputs() {
+        let mock = MockTransport::new();
+        let mut report = [0u8; 1];
+
+        assert_eq!(
+            mock.read_report(&mut report).unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock
+        );
+
+        mock.push_output([0x02]);
+        assert_eq!(mock.read_report(&mut report).unwrap(), 1);
+        assert_eq!(report, [0x02]);
+    }
+
+    #[test]
+    fn simulates_faults() {
+        let mock = MockTransport::new();
+
+        mock.fail_write(Fault::Again);
+        mock.fail_write(Fault::Shutdown);
+        mock.fail_write(Fault::Short(2));
+
+        assert_eq!(
+            mock.write_report(&[1, 2, 3]).unwrap_err().raw_os_error(),
+            Some(libc::EAGAIN)
+        );
+        assert_eq!(
+            mock.write_report(&[1, 2, 3]).unwrap_err().raw_os_error(),
+            Some(libc::ESHUTDOWN)
+        );
+        assert_eq!(mock.write_report(&[1, 2, 3]).unwrap(), 2);
+        assert_eq!(mock.write_report(&[1, 2, 3]).unwrap(), 3);
+        assert_eq!(mock.inputs(), [vec![1, 2], vec![1, 2, 3]]);
+
+        mock.push_output([1, 2]);
+        mock.fail_read(Fault::Os(libc::EIO));
+        mock.fail_read(Fault::Short(1));
+
+        let mut report = [0u8; 2];
+        assert_eq!(
+            mock.read_report(&mut report).unwrap_err().raw_os_error(),
+            Some(libc::EIO)
+        );
+        assert_eq!(mock.read_report(&mut report).unwrap(), 1);
+    }
+}
@@ -5,7 +5,7 @@ use static_assertions::const_assert_eq;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{Class, StateChange, ValueChange};
+use crate::{Boot, Class, EmptyReport, RawReport, StateChange, ValueChange};
 
 /// Mouse HID class
 #[derive(Clone, Copy, Debug)]
@@ -14,6 +14,7 @@ pub struct Mouse;
 impl Class for Mouse {
     type Input = MouseInput;
     type Output = MouseOutput;
+    type Feature = EmptyReport;
 
     fn input(&self) -> Self::Input {
         Self::Input::default()
@@ -22,6 +23,18 @@ impl Class for Mouse {
     fn output(&self) -> Self::Output {
         Self::Output::default()
     }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl Boot for Mouse {
+    type BootInput = BootMouseInput;
+
+    fn boot_input(input: &Self::Input) -> Self::BootInput {
+        BootMouseInput::new(input.mods(), input.pointer())
+    }
 }
 
 impl AsRef<str> for Mouse {
@@ -54,6 +67,25 @@ bitflags! {
         ///
         /// Usually middle.
         const Tertiary = 0x04;
+
+        /// Back button
+        ///
+        /// Usually side button under thumb.
+        const Back = 0x08;
+
+        /// Forward button
+        ///
+        /// Usually second side button under thumb.
+        const Forward = 0x10;
+
+        /// Sixth button
+        const Sixth = 0x20;
+
+        /// Seventh button
+        const Seventh = 0x40;
+
+        /// Eighth button
+        const Eighth = 0x80;
     }
 }
 
@@ -80,8 +112,10 @@ impl From<Buttons> for u8 {
 
 code_enum! {
     /// Button code
+    #[derive(Default)]
     Button: u8 {
         /// No button
+        #[default]
         None = 0x00 => "none" | "0",
 
         /// Primary button
@@ -98,13 +132,32 @@ code_enum! {
         ///
         /// Usually middle.
         Tertiary = 0x03 => "tertiary" | "third" | "3",
+
+        /// Back button
+        ///
+        /// Usually side button under thumb.
+        Back = 0x04 => "back" | "fourth" | "4",
+
+        /// Forward button
+        ///
+        /// Usually second side button under thumb.
+        Forward = 0x05 => "forward" | "fifth" | "5",
+
+        /// Sixth button
+        Sixth = 0x06 => "sixth" | "6",
+
+        /// Seventh button
+        Seventh = 0x07 => "seventh" | "7",
+
+        /// Eighth button
+        Eighth = 0x08 => "eighth" | "8",
     }
 }
 
 impl From<Buttons> for Button {
     fn from(mods: Buttons) -> Self {
         let off = mods.bits().trailing_zeros() as u8;
-        if off < 3 {
+        if off < 8 {
             Button::from(off + 1)
         } else {
             Button::None
@@ -122,7 +175,7 @@ impl From<Button> for Buttons {
 impl Button {
     /// Converts from raw value safely
     pub fn safe_from(raw: u8) -> Option<Self> {
-        if raw <= 3 {
+        if raw <= 8 {
             Some(From::from(raw))
         } else {
             None
@@ -130,22 +183,75 @@ impl Button {
     }
 }
 
-impl Default for Button {
-    fn default() -> Self {
-        Self::None
+code_enum! {
+    /// Axis code
+    ///
+    /// Generic desktop usage of pointer axis.
+    Axis: u8 {
+        /// Horizontal pointer axis
+        X = 0x30 => "x",
+        /// Vertical pointer axis
+        Y = 0x31 => "y",
+        /// Vertical wheel
+        Wheel = 0x38 => "wheel",
+    }
+}
+
+impl Axis {
+    /// Converts from raw value safely
+    pub fn safe_from(raw: u8) -> Option<Self> {
+        if matches!(raw, 0x30 | 0x31 | 0x38) {
+            Some(From::from(raw))
+        } else {
+            None
+        }
     }
 }
 
 serde_num! {
     Buttons: u8, "a button mask";
     Button: u8, "a numeric button code";
+    Axis: u8, "a numeric axis code";
 }
 
-/// Mouse input report
+/// Wheel value of mouse input report
+pub trait WheelValue: Copy + Default + PartialEq + core::fmt::Debug {
+    /// Add value saturating at bounds
+    fn saturating_add(self, other: Self) -> Self;
+
+    /// Subtract value saturating at bounds
+    fn saturating_sub(self, other: Self) -> Self;
+}
+
+macro_rules! wheel_value {
+    ($($type:ty)*) => {
+        $(
+            impl WheelValue for $type {
+                fn saturating_add(self, other: Self) -> Self {
+                    <$type>::saturating_add(self, other)
+                }
+
+                fn saturating_sub(self, other: Self) -> Self {
+                    <$type>::saturating_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+wheel_value!(i8 i16);
+
+/// Mouse input report with wheel value of given type
+///
+/// Base of mouse reports which have the same buttons and pointer layout.
 #[derive(Clone, Copy, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(serialize = "W: Serialize + Copy", deserialize = "W: Deserialize<'de>"))
+)]
 #[repr(C, packed)]
-pub struct MouseInput {
+pub struct GenericMouseInput<W> {
     /// Pressed buttons
     #[cfg_attr(feature = "serde", serde(rename = "b"))]
     button: Buttons,
@@ -154,12 +260,15 @@ pub struct MouseInput {
     pointer: (i16, i16),
     /// Mouse wheel coordinate
     #[cfg_attr(feature = "serde", serde(rename = "w"))]
-    wheel: i8,
+    wheel: W,
 }
 
+/// Mouse input report
+pub type MouseInput = GenericMouseInput<i8>;
+
 const_assert_eq!(size_of::<MouseInput>(), 6);
 
-impl MouseInput {
+impl<W: WheelValue> GenericMouseInput<W> {
     /// Get button mask
     pub fn mods(&self) -> Buttons {
         self.button
@@ -173,7 +282,7 @@ impl MouseInput {
     /// Get iterator over pressed buttons
     pub fn pressed(&self) -> PressedButtons<'_> {
         PressedButtons {
-            report: self,
+            buttons: &self.button,
             element: 0,
         }
     }
@@ -223,41 +332,50 @@ impl MouseInput {
     }
 
     /// Change XY coordinates
+    ///
+    /// Relative change saturates at coordinate bounds.
     pub fn change_pointer(&mut self, pointer: (i16, i16), relative: bool) {
         if relative {
-            self.pointer = (self.pointer.0 + pointer.0, self.pointer.1 + pointer.1);
+            self.pointer = (
+                self.pointer.0.saturating_add(pointer.0),
+                self.pointer.1.saturating_add(pointer.1),
+            );
         } else {
             self.pointer = pointer;
         }
     }
 
     /// Get wheel value
-    pub fn wheel(&self) -> i8 {
+    pub fn wheel(&self) -> W {
         self.wheel
     }
 
     /// Set wheel value
-    pub fn set_wheel(&mut self, wheel: i8) {
+    pub fn set_wheel(&mut self, wheel: W) {
         self.wheel = wheel;
     }
 
     /// Change wheel value
-    pub fn change_wheel(&mut self, wheel: i8, relative: bool) {
+    ///
+    /// Relative change saturates at wheel bounds.
+    pub fn change_wheel(&mut self, wheel: W, relative: bool) {
         if relative {
-            self.wheel += wheel;
+            self.wheel = self.wheel.saturating_add(wheel);
         } else {
             self.wheel = wheel;
         }
     }
 
     /// Change mouse input
-    pub fn change(&mut self, change: &MouseInputChange) {
+    pub fn change(&mut self, change: &GenericMouseInputChange<W>) {
         match change {
-            MouseInputChange::Button(change) => self.change_button(**change, change.state()),
-            MouseInputChange::Pointer(change) => {
+            GenericMouseInputChange::Button(change) => self.change_button(**change, change.state()),
+            GenericMouseInputChange::Pointer(change) => {
                 self.change_pointer(**change, change.is_relative())
             }
-            MouseInputChange::Wheel(change) => self.change_wheel(**change, change.is_relative()),
+            GenericMouseInputChange::Wheel(change) => {
+                self.change_wheel(**change, change.is_relative())
+            }
         }
     }
 
@@ -269,8 +387,8 @@ impl MouseInput {
         other: &'i Self,
         relative_pointer: bool,
         relative_wheel: bool,
-    ) -> MouseInputChanges<'i> {
-        MouseInputChanges {
+    ) -> GenericMouseInputChanges<'i, W> {
+        GenericMouseInputChanges {
             new: self,
             old: other,
             element: 0,
@@ -280,8 +398,28 @@ impl MouseInput {
     }
 }
 
-impl<'i> core::ops::Sub<&'i MouseInput> for &'i MouseInput {
-    type Output = MouseInputChanges<'i>;
+impl MouseInput {
+    /// Split relative pointer move into reports with in-range deltas
+    ///
+    /// Each report keeps buttons of this report and has zero wheel delta.
+    /// By default deltas is limited to ±127 which is accepted by boot protocol mice.
+    pub fn split_motion(&self, delta: (i32, i32)) -> MouseMotion {
+        MouseMotion {
+            input: MouseInput {
+                button: self.button,
+                ..Default::default()
+            },
+            delta,
+            done: (0, 0),
+            limit: i8::MAX as _,
+            step: 0,
+            curve: |_| i16::MAX,
+        }
+    }
+}
+
+impl<'i, W: WheelValue> core::ops::Sub<&'i GenericMouseInput<W>> for &'i GenericMouseInput<W> {
+    type Output = GenericMouseInputChanges<'i, W>;
 
     fn sub(self, other: Self) -> Self::Output {
         self.diff(other, false, false)
@@ -290,26 +428,32 @@ impl<'i> core::ops::Sub<&'i MouseInput> for &'i MouseInput {
 
 /// Change between mouse input reports
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum MouseInputChange {
+pub enum GenericMouseInputChange<W> {
     /// Button state change
     Button(StateChange<Button>),
     /// Pointer coordinates change
     Pointer(ValueChange<(i16, i16)>),
     /// Wheel value change
-    Wheel(ValueChange<i8>),
+    Wheel(ValueChange<W>),
 }
 
+/// Change between mouse input reports
+pub type MouseInputChange = GenericMouseInputChange<i8>;
+
 /// Changes between mouse input reports
-pub struct MouseInputChanges<'i> {
-    new: &'i MouseInput,
-    old: &'i MouseInput,
+pub struct GenericMouseInputChanges<'i, W> {
+    new: &'i GenericMouseInput<W>,
+    old: &'i GenericMouseInput<W>,
     element: u8,
     relative_pointer: bool,
     relative_wheel: bool,
 }
 
-impl<'i> Iterator for MouseInputChanges<'i> {
-    type Item = MouseInputChange;
+/// Changes between mouse input reports
+pub type MouseInputChanges<'i> = GenericMouseInputChanges<'i, i8>;
+
+impl<'i, W: WheelValue> Iterator for GenericMouseInputChanges<'i, W> {
+    type Item = GenericMouseInputChange<W>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -320,7 +464,7 @@ impl<'i> Iterator for MouseInputChanges<'i> {
                 if Buttons::empty() != ((self.new.button ^ self.old.button) & buttons) {
                     let button = Button::from(buttons);
                     let old_button = Button::from(self.old.button & buttons);
-                    return Some(MouseInputChange::Button(StateChange::new(
+                    return Some(GenericMouseInputChange::Button(StateChange::new(
                         button,
                         matches!(old_button, Button::None),
                     )));
@@ -331,11 +475,11 @@ impl<'i> Iterator for MouseInputChanges<'i> {
                 if self.new.pointer.0 != self.old.pointer.0
                     || self.new.pointer.1 != self.old.pointer.1
                 {
-                    return Some(MouseInputChange::Pointer(ValueChange::new(
+                    return Some(GenericMouseInputChange::Pointer(ValueChange::new(
                         if self.relative_pointer {
                             (
-                                self.new.pointer.0 - self.old.pointer.0,
-                                self.new.pointer.1 - self.old.pointer.1,
+                                self.new.pointer.0.saturating_sub(self.old.pointer.0),
+                                self.new.pointer.1.saturating_sub(self.old.pointer.1),
                             )
                         } else {
                             (self.new.pointer.0, self.new.pointer.1)
@@ -345,12 +489,13 @@ impl<'i> Iterator for MouseInputChanges<'i> {
                 }
             } else if self.element < 10 {
                 self.element += 1;
-                if self.new.wheel != self.old.wheel {
-                    return Some(MouseInputChange::Wheel(ValueChange::new(
+                let (new, old) = (self.new.wheel, self.old.wheel);
+                if new != old {
+                    return Some(GenericMouseInputChange::Wheel(ValueChange::new(
                         if self.relative_wheel {
-                            self.new.wheel - self.old.wheel
+                            new.saturating_sub(old)
                         } else {
-                            self.new.wheel
+                            new
                         },
                         self.relative_wheel,
                     )));
@@ -362,7 +507,7 @@ impl<'i> Iterator for MouseInputChanges<'i> {
     }
 }
 
-impl Extend<StateChange<Buttons>> for MouseInput {
+impl<W: WheelValue> Extend<StateChange<Buttons>> for GenericMouseInput<W> {
     fn extend<T>(&mut self, iter: T)
     where
         T: IntoIterator<Item = StateChange<Buttons>>,
@@ -373,7 +518,7 @@ impl Extend<StateChange<Buttons>> for MouseInput {
     }
 }
 
-impl Extend<StateChange<Button>> for MouseInput {
+impl<W: WheelValue> Extend<StateChange<Button>> for GenericMouseInput<W> {
     fn extend<T>(&mut self, iter: T)
     where
         T: IntoIterator<Item = StateChange<Button>>,
@@ -384,7 +529,7 @@ impl Extend<StateChange<Button>> for MouseInput {
     }
 }
 
-impl Extend<ValueChange<(i16, i16)>> for MouseInput {
+impl<W: WheelValue> Extend<ValueChange<(i16, i16)>> for GenericMouseInput<W> {
     fn extend<T>(&mut self, iter: T)
     where
         T: IntoIterator<Item = ValueChange<(i16, i16)>>,
@@ -395,7 +540,7 @@ impl Extend<ValueChange<(i16, i16)>> for MouseInput {
     }
 }
 
-impl Extend<(i16, i16)> for MouseInput {
+impl<W: WheelValue> Extend<(i16, i16)> for GenericMouseInput<W> {
     fn extend<T>(&mut self, iter: T)
     where
         T: IntoIterator<Item = (i16, i16)>,
@@ -406,10 +551,10 @@ impl Extend<(i16, i16)> for MouseInput {
     }
 }
 
-impl Extend<ValueChange<i8>> for MouseInput {
+impl<W: WheelValue> Extend<ValueChange<W>> for GenericMouseInput<W> {
     fn extend<T>(&mut self, iter: T)
     where
-        T: IntoIterator<Item = ValueChange<i8>>,
+        T: IntoIterator<Item = ValueChange<W>>,
     {
         for ValueChange { data, relative } in iter {
             self.change_wheel(data, relative)
@@ -417,10 +562,10 @@ impl Extend<ValueChange<i8>> for MouseInput {
     }
 }
 
-impl Extend<MouseInput> for MouseInput {
+impl<W> Extend<GenericMouseInput<W>> for GenericMouseInput<W> {
     fn extend<T>(&mut self, iter: T)
     where
-        T: IntoIterator<Item = MouseInput>,
+        T: IntoIterator<Item = GenericMouseInput<W>>,
     {
         for item in iter {
             *self = item;
@@ -428,9 +573,389 @@ impl Extend<MouseInput> for MouseInput {
     }
 }
 
+/// Boot protocol mouse input report
+///
+/// Has only three buttons and pointer deltas in range of ±127 without wheel.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct BootMouseInput {
+    /// Pressed buttons
+    #[cfg_attr(feature = "serde", serde(rename = "b"))]
+    button: Buttons,
+    /// Pointer deltas
+    #[cfg_attr(feature = "serde", serde(rename = "p"))]
+    pointer: (i8, i8),
+}
+
+const_assert_eq!(size_of::<BootMouseInput>(), 3);
+
+impl BootMouseInput {
+    /// Create report which saturates pointer deltas and drops extra buttons
+    pub fn new(buttons: Buttons, pointer: (i16, i16)) -> Self {
+        let saturate = |value: i16| value.clamp(i8::MIN as _, i8::MAX as _) as i8;
+        Self {
+            button: buttons & (Buttons::Primary | Buttons::Secondary | Buttons::Tertiary),
+            pointer: (saturate(pointer.0), saturate(pointer.1)),
+        }
+    }
+
+    /// Get button mask
+    pub fn mods(&self) -> Buttons {
+        self.button
+    }
+
+    /// Get pointer deltas
+    pub fn pointer(&self) -> (i8, i8) {
+        self.pointer
+    }
+}
+
+/// Mouse with horizontal wheel HID class
+#[derive(Clone, Copy, Debug)]
+pub struct PanMouse;
+
+impl Class for PanMouse {
+    type Input = PanMouseInput;
+    type Output = MouseOutput;
+    type Feature = EmptyReport;
+
+    fn input(&self) -> Self::Input {
+        Self::Input::default()
+    }
+
+    fn output(&self) -> Self::Output {
+        Self::Output::default()
+    }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl Boot for PanMouse {
+    type BootInput = BootMouseInput;
+
+    fn boot_input(input: &Self::Input) -> Self::BootInput {
+        BootMouseInput::new(input.mods(), input.pointer())
+    }
+}
+
+impl AsRef<str> for PanMouse {
+    fn as_ref(&self) -> &str {
+        "pan-mouse"
+    }
+}
+
+impl core::fmt::Display for PanMouse {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// Mouse input report with horizontal wheel
+///
+/// Same as [`MouseInput`] followed by AC Pan value of consumer page.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct PanMouseInput {
+    /// Buttons, pointer and vertical wheel
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: MouseInput,
+    /// Horizontal wheel coordinate
+    #[cfg_attr(feature = "serde", serde(rename = "h"))]
+    pan: i8,
+}
+
+const_assert_eq!(size_of::<PanMouseInput>(), 7);
+
+deref_impl! {
+    PanMouseInput => base: MouseInput,
+}
+
+impl PanMouseInput {
+    /// Get horizontal wheel value
+    pub fn pan(&self) -> i8 {
+        self.pan
+    }
+
+    /// Set horizontal wheel value
+    pub fn set_pan(&mut self, pan: i8) {
+        self.pan = pan;
+    }
+
+    /// Change horizontal wheel value
+    ///
+    /// Relative change saturates at wheel bounds.
+    pub fn change_pan(&mut self, pan: i8, relative: bool) {
+        if relative {
+            self.pan = self.pan.saturating_add(pan);
+        } else {
+            self.pan = pan;
+        }
+    }
+}
+
+impl From<MouseInput> for PanMouseInput {
+    fn from(base: MouseInput) -> Self {
+        Self { base, pan: 0 }
+    }
+}
+
+/// Mouse with high-resolution wheels HID class
+///
+/// Host negotiates resolution of wheels using Resolution Multiplier feature report.
+/// Gadget function should be configured with [`HiResMouse::DESCRIPTOR`] as report descriptor.
+#[derive(Clone, Copy, Debug)]
+pub struct HiResMouse;
+
+impl HiResMouse {
+    /// Report descriptor of mouse with eight buttons and high-resolution wheels
+    pub const DESCRIPTOR: [u8; 121] = [
+        0x05, 0x01, // Usage Page (Generic Desktop)
+        0x09, 0x02, // Usage (Mouse)
+        0xa1, 0x01, // Collection (Application)
+        0x09, 0x01, //   Usage (Pointer)
+        0xa1, 0x00, //   Collection (Physical)
+        0x05, 0x09, //     Usage Page (Button)
+        0x19, 0x01, //     Usage Minimum (1)
+        0x29, 0x08, //     Usage Maximum (8)
+        0x15, 0x00, //     Logical Minimum (0)
+        0x25, 0x01, //     Logical Maximum (1)
+        0x75, 0x01, //     Report Size (1)
+        0x95, 0x08, //     Report Count (8)
+        0x81, 0x02, //     Input (Data,Var,Abs)
+        0x05, 0x01, //     Usage Page (Generic Desktop)
+        0x09, 0x30, //     Usage (X)
+        0x09, 0x31, //     Usage (Y)
+        0x16, 0x01, 0x80, //     Logical Minimum (-32767)
+        0x26, 0xff, 0x7f, //     Logical Maximum (32767)
+        0x75, 0x10, //     Report Size (16)
+        0x95, 0x02, //     Report Count (2)
+        0x81, 0x06, //     Input (Data,Var,Rel)
+        0xa1, 0x02, //     Collection (Logical)
+        0x09, 0x48, //       Usage (Resolution Multiplier)
+        0x15, 0x00, //       Logical Minimum (0)
+        0x25, 0x01, //       Logical Maximum (1)
+        0x35, 0x01, //       Physical Minimum (1)
+        0x45, 0x78, //       Physical Maximum (120)
+        0x75, 0x02, //       Report Size (2)
+        0x95, 0x01, //       Report Count (1)
+        0xb1, 0x02, //       Feature (Data,Var,Abs)
+        0x35, 0x00, //       Physical Minimum (0)
+        0x45, 0x00, //       Physical Maximum (0)
+        0x09, 0x38, //       Usage (Wheel)
+        0x16, 0x01, 0x80, //       Logical Minimum (-32767)
+        0x26, 0xff, 0x7f, //       Logical Maximum (32767)
+        0x75, 0x10, //       Report Size (16)
+        0x81, 0x06, //       Input (Data,Var,Rel)
+        0xc0, //     End Collection
+        0xa1, 0x02, //     Collection (Logical)
+        0x09, 0x48, //       Usage (Resolution Multiplier)
+        0x15, 0x00, //       Logical Minimum (0)
+        0x25, 0x01, //       Logical Maximum (1)
+        0x35, 0x01, //       Physical Minimum (1)
+        0x45, 0x78, //       Physical Maximum (120)
+        0x75, 0x02, //       Report Size (2)
+        0xb1, 0x02, //       Feature (Data,Var,Abs)
+        0x35, 0x00, //       Physical Minimum (0)
+        0x45, 0x00, //       Physical Maximum (0)
+        0x05, 0x0c, //       Usage Page (Consumer)
+        0x0a, 0x38, 0x02, //       Usage (AC Pan)
+        0x16, 0x01, 0x80, //       Logical Minimum (-32767)
+        0x26, 0xff, 0x7f, //       Logical Maximum (32767)
+        0x75, 0x10, //       Report Size (16)
+        0x81, 0x06, //       Input (Data,Var,Rel)
+        0xc0, //     End Collection
+        0x75, 0x04, //     Report Size (4)
+        0xb1, 0x03, //     Feature (Const)
+        0xc0, //   End Collection
+        0xc0, // End Collection
+    ];
+}
+
+impl Class for HiResMouse {
+    type Input = HiResMouseInput;
+    type Output = MouseOutput;
+    type Feature = ResolutionMultiplier;
+
+    fn input(&self) -> Self::Input {
+        Self::Input::default()
+    }
+
+    fn output(&self) -> Self::Output {
+        Self::Output::default()
+    }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl Boot for HiResMouse {
+    type BootInput = BootMouseInput;
+
+    fn boot_input(input: &Self::Input) -> Self::BootInput {
+        BootMouseInput::new(input.mods(), input.pointer())
+    }
+}
+
+impl AsRef<str> for HiResMouse {
+    fn as_ref(&self) -> &str {
+        "hires-mouse"
+    }
+}
+
+impl core::fmt::Display for HiResMouse {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// High-resolution mouse input report
+///
+/// Same as [`MouseInput`] with 16-bit wheel followed by 16-bit AC Pan value of consumer page.
+/// Wheel and AC Pan values is in counts of `1 / multiplier` notch.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct HiResMouseInput {
+    /// Buttons, pointer and vertical wheel counts
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    base: GenericMouseInput<i16>,
+    /// Horizontal wheel counts
+    #[cfg_attr(feature = "serde", serde(rename = "h"))]
+    pan: i16,
+}
+
+const_assert_eq!(size_of::<HiResMouseInput>(), 9);
+
+deref_impl! {
+    HiResMouseInput => base: GenericMouseInput<i16>,
+}
+
+impl HiResMouseInput {
+    /// Get horizontal wheel counts
+    pub fn pan(&self) -> i16 {
+        self.pan
+    }
+
+    /// Set horizontal wheel counts
+    pub fn set_pan(&mut self, pan: i16) {
+        self.pan = pan;
+    }
+
+    /// Change horizontal wheel counts
+    ///
+    /// Relative change saturates at wheel bounds.
+    pub fn change_pan(&mut self, pan: i16, relative: bool) {
+        if relative {
+            self.pan = self.pan.saturating_add(pan);
+        } else {
+            self.pan = pan;
+        }
+    }
+}
+
+impl From<GenericMouseInput<i16>> for HiResMouseInput {
+    fn from(base: GenericMouseInput<i16>) -> Self {
+        Self { base, pan: 0 }
+    }
+}
+
+/// Resolution Multiplier feature report
+///
+/// Expects descriptor where vertical and horizontal Resolution Multiplier fields is two bits
+/// each (vertical is lowest) with logical range `0..=1` and physical range `1..=MAX`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct ResolutionMultiplier {
+    raw: u8,
+}
+
+const_assert_eq!(size_of::<ResolutionMultiplier>(), 1);
+
+impl ResolutionMultiplier {
+    /// Physical maximum of multiplier
+    pub const MAX: u16 = 120;
+
+    /// Create report with given wheels resolution enabled
+    pub fn new(wheel: bool, pan: bool) -> Self {
+        Self {
+            raw: wheel as u8 | (pan as u8) << 2,
+        }
+    }
+
+    /// Get vertical wheel multiplier
+    pub fn wheel(&self) -> u16 {
+        if self.raw & 0x03 != 0 {
+            Self::MAX
+        } else {
+            1
+        }
+    }
+
+    /// Get horizontal wheel multiplier
+    pub fn pan(&self) -> u16 {
+        if self.raw & 0x0c != 0 {
+            Self::MAX
+        } else {
+            1
+        }
+    }
+}
+
+/// Converter of fractional wheel notches into high-resolution counts
+///
+/// Fraction of count which cannot be sent is kept and added to next scroll.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Scroller {
+    multiplier: ResolutionMultiplier,
+    remainder: (f32, f32),
+}
+
+impl Scroller {
+    /// Create scroller for given resolution
+    pub fn new(multiplier: ResolutionMultiplier) -> Self {
+        Self {
+            multiplier,
+            remainder: (0.0, 0.0),
+        }
+    }
+
+    /// Get current resolution
+    pub fn multiplier(&self) -> ResolutionMultiplier {
+        self.multiplier
+    }
+
+    /// Change resolution when host sets new one
+    ///
+    /// Kept fractions is discarded.
+    pub fn set_multiplier(&mut self, multiplier: ResolutionMultiplier) {
+        *self = Self::new(multiplier);
+    }
+
+    /// Put vertical and horizontal scroll in notches to report
+    pub fn scroll(&mut self, input: &mut HiResMouseInput, notches: (f32, f32)) {
+        let wheel = counts(&mut self.remainder.0, notches.0, self.multiplier.wheel());
+        let pan = counts(&mut self.remainder.1, notches.1, self.multiplier.pan());
+        input.set_wheel(wheel);
+        input.set_pan(pan);
+    }
+}
+
+fn counts(remainder: &mut f32, notches: f32, multiplier: u16) -> i16 {
+    let value = notches * multiplier as f32 + *remainder;
+    let counts = value.trunc().clamp(i16::MIN as _, i16::MAX as _);
+    *remainder = value - counts;
+    counts as _
+}
+
 /// An iterator over pressed buttons
 pub struct PressedButtons<'i> {
-    report: &'i MouseInput,
+    buttons: &'i Buttons,
     element: u8,
 }
 
@@ -441,7 +966,7 @@ impl<'i> Iterator for PressedButtons<'i> {
         while self.element < 8 {
             let mask = Buttons::from_bits_retain(1u8 << self.element);
             self.element += 1;
-            if self.report.button.contains(mask) {
+            if self.buttons.contains(mask) {
                 return Some(mask.into());
             }
         }
@@ -449,6 +974,70 @@ impl<'i> Iterator for PressedButtons<'i> {
     }
 }
 
+/// An iterator over reports of split pointer move
+///
+/// Pointer goes along straight line and last report ends exactly at requested delta.
+#[derive(Clone, Debug)]
+pub struct MouseMotion<F = fn(usize) -> i16> {
+    input: MouseInput,
+    delta: (i32, i32),
+    done: (i32, i32),
+    limit: i16,
+    step: usize,
+    curve: F,
+}
+
+impl<F> MouseMotion<F> {
+    /// Set maximum delta per report
+    pub fn limit(mut self, limit: i16) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// Set acceleration curve
+    ///
+    /// Curve maps index of report to maximum delta of this report,
+    /// which is further limited by [`MouseMotion::limit`].
+    pub fn accelerate<G: FnMut(usize) -> i16>(self, curve: G) -> MouseMotion<G> {
+        MouseMotion {
+            input: self.input,
+            delta: self.delta,
+            done: self.done,
+            limit: self.limit,
+            step: self.step,
+            curve,
+        }
+    }
+}
+
+impl<F: FnMut(usize) -> i16> Iterator for MouseMotion<F> {
+    type Item = MouseInput;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (dx, dy) = self.delta;
+        let major = dx.unsigned_abs().max(dy.unsigned_abs()) as i64;
+        let done = self.done.0.unsigned_abs().max(self.done.1.unsigned_abs()) as i64;
+        if done >= major {
+            return None;
+        }
+
+        let step = (self.curve)(self.step).clamp(1, self.limit) as i64;
+        self.step += 1;
+        let done = (done + step).min(major);
+
+        // round to nearest point of line to keep minor axis in sync with major one
+        let along = |delta: i32| {
+            let delta = delta as i64;
+            ((2 * delta * done + delta.signum() * major) / (2 * major)) as i32
+        };
+        let next = (along(dx), along(dy));
+        let mut input = self.input;
+        input.set_pointer(((next.0 - self.done.0) as _, (next.1 - self.done.1) as _));
+        self.done = next;
+        Some(input)
+    }
+}
+
 /// Keyboard output report
 #[derive(Clone, Copy, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -460,10 +1049,37 @@ const_assert_eq!(size_of::<MouseOutput>(), 0);
 raw_ref! {
     Buttons;
     Button;
+    Axis;
     MouseInput;
+    BootMouseInput;
+    PanMouseInput;
+    GenericMouseInput<i16>;
+    HiResMouseInput;
+    ResolutionMultiplier;
     MouseOutput;
 }
 
+// button and axis codes is sparse so only reports of masks and integers accept arbitrary bytes
+raw_mut! {
+    Buttons;
+    MouseInput;
+    BootMouseInput;
+    PanMouseInput;
+    GenericMouseInput<i16>;
+    HiResMouseInput;
+    ResolutionMultiplier;
+    MouseOutput;
+}
+
+unsafe impl RawReport for Buttons {}
+unsafe impl RawReport for MouseInput {}
+unsafe impl RawReport for BootMouseInput {}
+unsafe impl RawReport for PanMouseInput {}
+unsafe impl RawReport for GenericMouseInput<i16> {}
+unsafe impl RawReport for HiResMouseInput {}
+unsafe impl RawReport for ResolutionMultiplier {}
+unsafe impl RawReport for MouseOutput {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -475,6 +1091,9 @@ mod test {
         assert_eq!(Button::from(Buttons::Primary), Button::Primary);
         assert_eq!(Button::from(Buttons::Secondary), Button::Secondary);
         assert_eq!(Button::from(Buttons::Tertiary), Button::Tertiary);
+        assert_eq!(Button::from(Buttons::Back), Button::Back);
+        assert_eq!(Button::from(Buttons::Eighth), Button::Eighth);
+        assert_eq!(Button::safe_from(9), None);
     }
 
     #[test]
@@ -483,6 +1102,123 @@ mod test {
         assert_eq!(Buttons::from(Button::Primary), Buttons::Primary);
         assert_eq!(Buttons::from(Button::Secondary), Buttons::Secondary);
         assert_eq!(Buttons::from(Button::Tertiary), Buttons::Tertiary);
+        assert_eq!(Buttons::from(Button::Forward), Buttons::Forward);
+        assert_eq!(Buttons::from(Button::Eighth), Buttons::Eighth);
+    }
+
+    #[test]
+    fn pressed_extra_buttons() {
+        let mut input = MouseInput::default();
+        input.press_button(Button::Primary);
+        input.press_button(Button::Back);
+        input.press_button(Button::Eighth);
+        assert_eq!(input.count_pressed(), 3);
+        assert_eq!(
+            input.pressed().collect::<Vec<_>>(),
+            [Button::Primary, Button::Back, Button::Eighth]
+        );
+
+        let old = MouseInput::default();
+        let mut changes = &input - &old;
+        assert_eq!(
+            changes.nth(2),
+            Some(MouseInputChange::Button(StateChange::press(Button::Eighth)))
+        );
+    }
+
+    #[test]
+    fn boot_mouse_input() {
+        let mut input = Mouse.input();
+        input.press_button(Button::Secondary);
+        input.press_button(Button::Back);
+        input.set_pointer((300, -5));
+        input.set_wheel(1);
+
+        let boot = Mouse::boot_input(&input);
+        assert_eq!(boot.mods(), Buttons::Secondary);
+        assert_eq!(boot.pointer(), (127, -5));
+        assert_eq!(boot.as_ref(), [0x02, 0x7f, 0xfb]);
+    }
+
+    #[test]
+    fn pan_mouse_input() {
+        let mut input = PanMouse.input();
+        input.press_button(Button::Forward);
+        input.set_pointer((1, -1));
+        input.set_wheel(-2);
+        input.change_pan(100, true);
+        input.change_pan(100, true);
+        assert_eq!(input.pan(), i8::MAX);
+        assert_eq!(input.as_ref(), [0x10, 1, 0, 0xff, 0xff, 0xfe, 0x7f]);
+    }
+
+    #[test]
+    fn hires_mouse_scroll() {
+        let mut input = HiResMouse.input();
+        input.press_button(Button::Primary);
+        let mut scroller = Scroller::default();
+
+        // low resolution scroll accumulates fractions until full notch
+        scroller.scroll(&mut input, (0.4, -0.5));
+        assert_eq!((input.wheel(), input.pan()), (0, 0));
+        scroller.scroll(&mut input, (0.4, -0.5));
+        assert_eq!((input.wheel(), input.pan()), (0, -1));
+        scroller.scroll(&mut input, (0.4, 0.0));
+        assert_eq!((input.wheel(), input.pan()), (1, 0));
+
+        let mut multiplier = HiResMouse.feature();
+        multiplier.as_mut().copy_from_slice(&[0x01]);
+        assert_eq!((multiplier.wheel(), multiplier.pan()), (120, 1));
+        assert_eq!(multiplier, ResolutionMultiplier::new(true, false));
+
+        scroller.set_multiplier(ResolutionMultiplier::new(true, true));
+        scroller.scroll(&mut input, (0.25, -1.5));
+        assert_eq!((input.wheel(), input.pan()), (30, -180));
+        assert_eq!(input.as_ref(), [1, 0, 0, 0, 0, 30, 0, 0x4c, 0xff]);
+        assert_eq!(input.pressed().collect::<Vec<_>>(), [Button::Primary]);
+    }
+
+    #[test]
+    fn hires_mouse_input() {
+        let old = HiResMouse.input();
+        let mut new = old;
+        new.press_button(Button::Back);
+        new.change_wheel(i16::MAX, true);
+        new.change_wheel(300, true);
+        new.change_pan(-200, true);
+        new.change_pan(-50, true);
+        assert_eq!((new.wheel(), new.pan()), (i16::MAX, -250));
+
+        let mut changes = &*new - &*old;
+        assert_eq!(
+            changes.next(),
+            Some(GenericMouseInputChange::Button(StateChange::press(
+                Button::Back
+            )))
+        );
+        assert_eq!(
+            changes.next(),
+            Some(GenericMouseInputChange::Wheel(ValueChange::new(
+                i16::MAX,
+                false
+            )))
+        );
+        assert_eq!(changes.next(), None);
+    }
+
+    #[test]
+    fn hires_mouse_descriptor() {
+        let descriptor = HiResMouse::DESCRIPTOR;
+        assert_eq!(descriptor[..4], [0x05, 0x01, 0x09, 0x02]);
+        assert_eq!(descriptor[descriptor.len() - 2..], [0xc0, 0xc0]);
+        // two multipliers in logical collections
+        assert_eq!(
+            descriptor
+                .windows(2)
+                .filter(|item| item == &[0x09, 0x48])
+                .count(),
+            2
+        );
     }
 
     #[test]
@@ -545,4 +1281,53 @@ mod test {
         );
         assert_eq!(changes.next(), None);
     }
+
+    #[test]
+    fn mouse_input_saturate() {
+        let mut input = MouseInput::default();
+
+        input.change_pointer((i16::MAX, -5), true);
+        input.change_pointer((1, i16::MIN), true);
+        assert_eq!(input.pointer(), (i16::MAX, i16::MIN));
+
+        input.change_wheel(-100, true);
+        input.change_wheel(-100, true);
+        assert_eq!(input.wheel(), i8::MIN);
+    }
+
+    #[test]
+    fn mouse_motion_split() {
+        let mut input = MouseInput::default();
+        input.press_button(Button::Primary);
+        input.set_wheel(3);
+
+        let steps = input.split_motion((300, -100)).collect::<Vec<_>>();
+        assert_eq!(
+            steps.iter().map(|step| step.pointer()).collect::<Vec<_>>(),
+            [(127, -42), (127, -43), (46, -15)]
+        );
+        assert!(steps
+            .iter()
+            .all(|step| step.mods() == Buttons::Primary && step.wheel() == 0));
+
+        let steps = MouseInput::default()
+            .split_motion((-70, 20))
+            .limit(50)
+            .accelerate(|step| 10 << step)
+            .map(|step| step.pointer())
+            .collect::<Vec<_>>();
+        assert_eq!(steps, [(-10, 3), (-20, 6), (-40, 11)]);
+
+        let sum =
+            MouseInput::default()
+                .split_motion((100_000, 99_999))
+                .fold((0, 0), |(x, y), step| {
+                    let (dx, dy) = step.pointer();
+                    assert!(dx.abs() <= 127 && dy.abs() <= 127);
+                    (x + dx as i32, y + dy as i32)
+                });
+        assert_eq!(sum, (100_000, 99_999));
+
+        assert_eq!(MouseInput::default().split_motion((0, 0)).count(), 0);
+    }
 }
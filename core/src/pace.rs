@@ -0,0 +1,229 @@
+//! Report pacing
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "keyboard")]
+use crate::KeyboardInput;
+
+#[cfg(feature = "mouse")]
+use crate::{HiResMouseInput, MouseInput, PanMouseInput};
+
+/// Input report which can absorb following report
+pub trait Coalesce {
+    /// Merge next report into this one
+    ///
+    /// Returns `false` when reports cannot be merged without losing state transitions.
+    fn coalesce(&mut self, next: &Self) -> bool;
+}
+
+#[cfg(feature = "mouse")]
+impl Coalesce for MouseInput {
+    /// Relative pointer and wheel deltas is summed while buttons is unchanged
+    fn coalesce(&mut self, next: &Self) -> bool {
+        if self.mods() != next.mods() {
+            return false;
+        }
+        let (x, y) = self.pointer();
+        let (dx, dy) = next.pointer();
+        let (Some(x), Some(y), Some(wheel)) = (
+            x.checked_add(dx),
+            y.checked_add(dy),
+            self.wheel().checked_add(next.wheel()),
+        ) else {
+            return false;
+        };
+        self.set_pointer((x, y));
+        self.set_wheel(wheel);
+        true
+    }
+}
+
+#[cfg(feature = "mouse")]
+impl Coalesce for PanMouseInput {
+    fn coalesce(&mut self, next: &Self) -> bool {
+        let Some(pan) = self.pan().checked_add(next.pan()) else {
+            return false;
+        };
+        if !MouseInput::coalesce(self, next) {
+            return false;
+        }
+        self.set_pan(pan);
+        true
+    }
+}
+
+#[cfg(feature = "mouse")]
+impl Coalesce for HiResMouseInput {
+    fn coalesce(&mut self, next: &Self) -> bool {
+        if self.mods() != next.mods() {
+            return false;
+        }
+        let ((x, y), (dx, dy)) = (self.pointer(), next.pointer());
+        let (Some(x), Some(y), Some(wheel), Some(pan)) = (
+            x.checked_add(dx),
+            y.checked_add(dy),
+            self.wheel().checked_add(next.wheel()),
+            self.pan().checked_add(next.pan()),
+        ) else {
+            return false;
+        };
+        self.set_pointer((x, y));
+        self.set_wheel(wheel);
+        self.set_pan(pan);
+        true
+    }
+}
+
+#[cfg(feature = "keyboard")]
+impl Coalesce for KeyboardInput {
+    /// Only repeated reports is merged because any change is key transition
+    fn coalesce(&mut self, next: &Self) -> bool {
+        AsRef::<[u8]>::as_ref(self) == AsRef::<[u8]>::as_ref(next)
+    }
+}
+
+/// Queue of input reports which is sent not faster than once per interval
+///
+/// Reports pushed while waiting is coalesced with last pending report when possible.
+#[derive(Clone, Debug)]
+pub struct Pacer<I> {
+    interval: Duration,
+    queue: VecDeque<I>,
+    next: Option<Instant>,
+}
+
+impl<I: Coalesce + Clone> Pacer<I> {
+    /// Create pacer with given interval
+    ///
+    /// Usually interval should match polling interval of endpoint (`bInterval`).
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            queue: VecDeque::new(),
+            next: None,
+        }
+    }
+
+    /// Get sending interval
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Set sending interval
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Get number of pending reports
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check that there is no pending reports
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Put report to queue
+    pub fn push(&mut self, report: &I) {
+        if let Some(last) = self.queue.back_mut() {
+            if last.coalesce(report) {
+                return;
+            }
+        }
+        self.queue.push_back(report.clone());
+    }
+
+    /// Get time when next pending report should be sent
+    pub fn deadline(&self) -> Option<Instant> {
+        if self.queue.is_empty() {
+            None
+        } else {
+            Some(self.next.unwrap_or_else(Instant::now))
+        }
+    }
+
+    /// Get next pending report if it should be sent at given time
+    pub fn peek(&self, now: Instant) -> Option<&I> {
+        if self.next.is_some_and(|next| next > now) {
+            return None;
+        }
+        self.queue.front()
+    }
+
+    /// Remove next pending report after it has been sent at given time
+    pub fn pop(&mut self, now: Instant) -> Option<I> {
+        let report = self.queue.pop_front()?;
+        self.next = Some(now + self.interval);
+        Some(report)
+    }
+}
+
+#[cfg(all(test, any(feature = "keyboard", feature = "mouse")))]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "mouse")]
+    #[test]
+    fn coalesce_mouse() {
+        use crate::Button;
+
+        let interval = Duration::from_millis(8);
+        let mut pacer = Pacer::new(interval);
+        let start = Instant::now();
+
+        let mut motion = MouseInput::default();
+        motion.set_pointer((3, -2));
+        pacer.push(&motion);
+        assert_eq!(pacer.peek(start).unwrap().pointer(), (3, -2));
+        pacer.pop(start);
+
+        pacer.push(&motion);
+        pacer.push(&motion);
+        motion.set_wheel(1);
+        pacer.push(&motion);
+        assert_eq!(pacer.len(), 1);
+        assert!(pacer.peek(start).is_none());
+        assert_eq!(pacer.deadline(), Some(start + interval));
+
+        let mut click = MouseInput::default();
+        click.press_button(Button::Primary);
+        pacer.push(&click);
+        click.set_pointer((i16::MAX, 0));
+        pacer.push(&click);
+        pacer.push(&click);
+        assert_eq!(pacer.len(), 3);
+
+        let report = pacer.peek(start + interval).unwrap();
+        assert_eq!(report.pointer(), (9, -6));
+        assert_eq!(report.wheel(), 1);
+        pacer.pop(start + interval);
+        assert!(pacer.peek(start + interval).is_none());
+
+        let report = pacer.pop(start + interval * 2).unwrap();
+        assert_eq!(report.mods(), Button::Primary.into());
+        assert_eq!(report.pointer(), (i16::MAX, 0));
+        assert_eq!(pacer.len(), 1);
+    }
+
+    #[cfg(feature = "keyboard")]
+    #[test]
+    fn coalesce_keyboard() {
+        use crate::Key;
+
+        let mut pacer = Pacer::new(Duration::from_millis(8));
+
+        let mut input = KeyboardInput::default();
+        input.press_key(Key::A);
+        pacer.push(&input);
+        pacer.push(&input);
+        input.release_key(Key::A);
+        pacer.push(&input);
+        input.press_key(Key::A);
+        pacer.push(&input);
+        assert_eq!(pacer.len(), 3);
+    }
+}
@@ -0,0 +1,256 @@
+use bitflags::bitflags;
+use core::{mem::size_of, time::Duration};
+use static_assertions::const_assert_eq;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Class, EmptyReport, RawReport};
+
+/// Power device (UPS) HID class
+///
+/// Battery status is sent as input reports and also can be read by host as feature report.
+/// Gadget function should be configured with [`PowerDevice::DESCRIPTOR`] as report descriptor.
+#[derive(Clone, Copy, Debug)]
+pub struct PowerDevice;
+
+impl PowerDevice {
+    /// Report descriptor of UPS with power summary collection
+    pub const DESCRIPTOR: [u8; 82] = [
+        0x05, 0x84, // Usage Page (Power Device)
+        0x09, 0x04, // Usage (UPS)
+        0xa1, 0x01, // Collection (Application)
+        0x09, 0x24, //   Usage (Power Summary)
+        0xa1, 0x00, //   Collection (Physical)
+        0x05, 0x85, //     Usage Page (Battery System)
+        0x15, 0x00, //     Logical Minimum (0)
+        0x25, 0x64, //     Logical Maximum (100)
+        0x75, 0x08, //     Report Size (8)
+        0x95, 0x01, //     Report Count (1)
+        0x09, 0x66, //     Usage (RemainingCapacity)
+        0x81, 0x02, //     Input (Data,Var,Abs)
+        0x09, 0x66, //     Usage (RemainingCapacity)
+        0xb1, 0x02, //     Feature (Data,Var,Abs)
+        0x27, 0xff, 0xff, 0x00, 0x00, //     Logical Maximum (65535)
+        0x75, 0x10, //     Report Size (16)
+        0x66, 0x01, 0x10, //     Unit (Seconds)
+        0x09, 0x68, //     Usage (RunTimeToEmpty)
+        0x81, 0x02, //     Input (Data,Var,Abs)
+        0x09, 0x68, //     Usage (RunTimeToEmpty)
+        0xb1, 0x02, //     Feature (Data,Var,Abs)
+        0x65, 0x00, //     Unit (None)
+        0x25, 0x01, //     Logical Maximum (1)
+        0x75, 0x01, //     Report Size (1)
+        0x95, 0x03, //     Report Count (3)
+        0x09, 0x44, //     Usage (Charging)
+        0x09, 0x45, //     Usage (Discharging)
+        0x09, 0xd0, //     Usage (ACPresent)
+        0x81, 0x02, //     Input (Data,Var,Abs)
+        0x95, 0x05, //     Report Count (5)
+        0x81, 0x03, //     Input (Const)
+        0x95, 0x03, //     Report Count (3)
+        0x09, 0x44, //     Usage (Charging)
+        0x09, 0x45, //     Usage (Discharging)
+        0x09, 0xd0, //     Usage (ACPresent)
+        0xb1, 0x02, //     Feature (Data,Var,Abs)
+        0x95, 0x05, //     Report Count (5)
+        0xb1, 0x03, //     Feature (Const)
+        0xc0, //   End Collection
+        0xc0, // End Collection
+    ];
+}
+
+impl Class for PowerDevice {
+    type Input = PowerStatus;
+    type Output = EmptyReport;
+    type Feature = PowerStatus;
+
+    fn input(&self) -> Self::Input {
+        Self::Input::default()
+    }
+
+    fn output(&self) -> Self::Output {
+        Self::Output::default()
+    }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl AsRef<str> for PowerDevice {
+    fn as_ref(&self) -> &str {
+        "power-device"
+    }
+}
+
+impl core::fmt::Display for PowerDevice {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+bitflags! {
+    /// Power state flags
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct PowerFlags: u8 {
+        /// Battery is charging
+        const Charging = 0x01;
+        /// Battery is discharging
+        const Discharging = 0x02;
+        /// AC power is present
+        const AcPresent = 0x04;
+    }
+}
+
+impl PowerFlags {
+    /// Converts from raw value safely
+    pub fn safe_from(raw: u8) -> Option<Self> {
+        Self::from_bits(raw)
+    }
+}
+
+impl From<PowerFlags> for u8 {
+    fn from(flags: PowerFlags) -> Self {
+        flags.bits()
+    }
+}
+
+serde_num! {
+    PowerFlags: u8, "a power state mask";
+}
+
+/// Power device status report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct PowerStatus {
+    /// Remaining capacity in percents
+    #[cfg_attr(feature = "serde", serde(rename = "c"))]
+    capacity: u8,
+    /// Run time to empty in seconds
+    #[cfg_attr(feature = "serde", serde(rename = "r"))]
+    runtime: u16,
+    /// Power state flags
+    #[cfg_attr(feature = "serde", serde(rename = "f"))]
+    flags: PowerFlags,
+}
+
+const_assert_eq!(size_of::<PowerStatus>(), 4);
+
+impl PowerStatus {
+    /// Get remaining capacity in percents
+    pub fn capacity(&self) -> u8 {
+        self.capacity
+    }
+
+    /// Set remaining capacity in percents
+    ///
+    /// Values above 100 is clamped.
+    pub fn set_capacity(&mut self, capacity: u8) {
+        self.capacity = capacity.min(100);
+    }
+
+    /// Get run time to empty
+    pub fn runtime(&self) -> Duration {
+        Duration::from_secs(u16::from_le(self.runtime) as _)
+    }
+
+    /// Set run time to empty
+    ///
+    /// Duration is truncated to whole seconds and saturated to about 18 hours.
+    pub fn set_runtime(&mut self, runtime: Duration) {
+        self.runtime = (runtime.as_secs().min(u16::MAX as u64) as u16).to_le();
+    }
+
+    /// Get power state flags
+    pub fn flags(&self) -> PowerFlags {
+        self.flags
+    }
+
+    /// Set power state flags
+    pub fn set_flags(&mut self, flags: PowerFlags) {
+        self.flags = flags;
+    }
+
+    /// Check that battery is charging
+    pub fn charging(&self) -> bool {
+        self.flags.contains(PowerFlags::Charging)
+    }
+
+    /// Set battery charging state
+    pub fn set_charging(&mut self, state: bool) {
+        self.flags.set(PowerFlags::Charging, state);
+    }
+
+    /// Check that battery is discharging
+    pub fn discharging(&self) -> bool {
+        self.flags.contains(PowerFlags::Discharging)
+    }
+
+    /// Set battery discharging state
+    pub fn set_discharging(&mut self, state: bool) {
+        self.flags.set(PowerFlags::Discharging, state);
+    }
+
+    /// Check that AC power is present
+    pub fn ac_present(&self) -> bool {
+        self.flags.contains(PowerFlags::AcPresent)
+    }
+
+    /// Set AC power presence
+    pub fn set_ac_present(&mut self, state: bool) {
+        self.flags.set(PowerFlags::AcPresent, state);
+    }
+}
+
+raw_ref! {
+    PowerStatus;
+}
+
+raw_mut! {
+    PowerStatus;
+}
+
+unsafe impl RawReport for PowerStatus {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn power_status() {
+        let mut status = PowerDevice.input();
+        status.set_capacity(150);
+        status.set_runtime(Duration::from_secs(3600));
+        status.set_discharging(true);
+        assert_eq!(status.capacity(), 100);
+        assert_eq!(status.runtime(), Duration::from_secs(3600));
+        assert!(status.discharging() && !status.charging() && !status.ac_present());
+        assert_eq!(status.as_ref(), [100, 0x10, 0x0e, 0x02]);
+
+        status.set_runtime(Duration::from_secs(100_000));
+        status.set_charging(true);
+        status.set_discharging(false);
+        status.set_ac_present(true);
+        assert_eq!(status.as_ref(), [100, 0xff, 0xff, 0x05]);
+    }
+
+    #[test]
+    fn power_runtime() {
+        let mut status = PowerDevice.input();
+        for secs in [0, 1, 0x1234, 0xff00, u16::MAX as u64] {
+            status.set_runtime(Duration::from_secs(secs));
+            assert_eq!(status.runtime(), Duration::from_secs(secs));
+            // report field is little-endian on any host
+            assert_eq!(status.as_ref()[1..3], (secs as u16).to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn power_descriptor() {
+        let descriptor = PowerDevice::DESCRIPTOR;
+        assert_eq!(descriptor[..4], [0x05, 0x84, 0x09, 0x04]);
+        assert_eq!(descriptor[descriptor.len() - 2..], [0xc0, 0xc0]);
+    }
+}
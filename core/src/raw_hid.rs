@@ -0,0 +1,102 @@
+use crate::{Class, EmptyReport, Error, Result};
+
+/// Vendor-defined HID class which exchanges raw byte packets
+///
+/// Input reports has `IN` bytes and output reports has `OUT` bytes.
+/// Gadget function should be configured with [`RawHid::DESCRIPTOR`] as report descriptor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RawHid<const IN: usize, const OUT: usize>;
+
+impl<const IN: usize, const OUT: usize> RawHid<IN, OUT> {
+    /// Report descriptor in vendor-defined usage page `0xFF00`
+    pub const DESCRIPTOR: [u8; 29] = descriptor(IN, OUT);
+
+    /// Create input report which starts with given data
+    ///
+    /// Rest of report is filled with zeros.
+    pub fn packet(data: &[u8]) -> Result<[u8; IN]> {
+        if data.len() > IN {
+            return Err(Error::invalid_report(format!(
+                "Data length {} exceeds report length {IN}",
+                data.len()
+            )));
+        }
+        let mut input = [0; IN];
+        input[..data.len()].copy_from_slice(data);
+        Ok(input)
+    }
+}
+
+const fn descriptor(input: usize, output: usize) -> [u8; 29] {
+    assert!(input <= u16::MAX as usize && output <= u16::MAX as usize);
+    let [input_lo, input_hi, ..] = input.to_le_bytes();
+    let [output_lo, output_hi, ..] = output.to_le_bytes();
+    [
+        0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+        0x09, 0x01, // Usage (0x01)
+        0xa1, 0x01, // Collection (Application)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x26, 0xff, 0x00, //   Logical Maximum (255)
+        0x75, 0x08, //   Report Size (8)
+        0x09, 0x02, //   Usage (0x02)
+        0x96, input_lo, input_hi, //   Report Count (IN)
+        0x81, 0x02, //   Input (Data,Var,Abs)
+        0x09, 0x03, //   Usage (0x03)
+        0x96, output_lo, output_hi, //   Report Count (OUT)
+        0x91, 0x02, //   Output (Data,Var,Abs)
+        0xc0, // End Collection
+    ]
+}
+
+impl<const IN: usize, const OUT: usize> Class for RawHid<IN, OUT> {
+    type Input = [u8; IN];
+    type Output = [u8; OUT];
+    type Feature = EmptyReport;
+
+    fn input(&self) -> Self::Input {
+        [0; IN]
+    }
+
+    fn output(&self) -> Self::Output {
+        [0; OUT]
+    }
+
+    fn feature(&self) -> Self::Feature {
+        EmptyReport::default()
+    }
+}
+
+impl<const IN: usize, const OUT: usize> AsRef<str> for RawHid<IN, OUT> {
+    fn as_ref(&self) -> &str {
+        "raw-hid"
+    }
+}
+
+impl<const IN: usize, const OUT: usize> core::fmt::Display for RawHid<IN, OUT> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn descriptor() {
+        let descriptor = RawHid::<64, 32>::DESCRIPTOR;
+        assert_eq!(descriptor[16..19], [0x96, 64, 0]);
+        assert_eq!(descriptor[23..26], [0x96, 32, 0]);
+        assert_eq!(RawHid::<300, 8>::DESCRIPTOR[16..19], [0x96, 0x2c, 0x01]);
+    }
+
+    #[test]
+    fn packet() {
+        let input = RawHid::<8, 8>::packet(&[1, 2, 3]).unwrap();
+        assert_eq!(input, [1, 2, 3, 0, 0, 0, 0, 0]);
+        assert!(matches!(
+            RawHid::<2, 8>::packet(&[1, 2, 3]),
+            Err(Error::InvalidReport(_))
+        ));
+    }
+}
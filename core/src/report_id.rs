@@ -0,0 +1,231 @@
+//! Report ID framing
+//!
+//! When one interface carries several report types each report is prefixed with report ID
+//! byte which is declared in report descriptor.
+
+use core::mem::{align_of, size_of};
+
+use crate::{Class, Error, RawReport, Result};
+
+/// Report prefixed with report ID
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct IdReport<R> {
+    id: u8,
+    report: R,
+}
+
+impl<R> IdReport<R> {
+    /// Create report with given ID
+    pub fn new(id: u8, report: R) -> Self {
+        Self { id, report }
+    }
+
+    /// Get report ID
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Get report without ID
+    pub fn into_inner(self) -> R {
+        self.report
+    }
+}
+
+deref_impl! {
+    IdReport<R> => report: R,
+}
+
+impl<R> IdReport<R> {
+    // report which is byte aligned immediately follows ID without padding
+    const LAYOUT: () = assert!(align_of::<R>() == 1 && size_of::<Self>() == 1 + size_of::<R>());
+}
+
+impl<R: AsRef<[u8]>> AsRef<[u8]> for IdReport<R> {
+    fn as_ref(&self) -> &[u8] {
+        let () = Self::LAYOUT;
+        // report bytes is initialized when report exposes all of them itself
+        let report = self.report.as_ref();
+        assert!(
+            core::ptr::eq(report.as_ptr(), &self.report as *const R as *const u8)
+                && report.len() == size_of::<R>(),
+            "Report bytes does not cover whole report"
+        );
+        unsafe { core::slice::from_raw_parts(self as *const _ as *const _, size_of::<Self>()) }
+    }
+}
+
+impl<R: RawReport> AsMut<[u8]> for IdReport<R> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        let () = Self::LAYOUT;
+        unsafe { core::slice::from_raw_parts_mut(self as *mut _ as *mut _, size_of::<Self>()) }
+    }
+}
+
+unsafe impl<R: RawReport> RawReport for IdReport<R> {}
+
+/// Class which reports is prefixed with report ID
+///
+/// The same ID is used for input, output and feature reports.
+#[derive(Clone, Copy, Debug)]
+pub struct Identified<C> {
+    class: C,
+    id: u8,
+}
+
+impl<C> Identified<C> {
+    /// Wrap class using given report ID
+    pub fn new(class: C, id: u8) -> Self {
+        Self { class, id }
+    }
+
+    /// Get report ID
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Get wrapped class
+    pub fn class(&self) -> &C {
+        &self.class
+    }
+}
+
+impl<C: Class> Class for Identified<C> {
+    type Input = IdReport<C::Input>;
+    type Output = IdReport<C::Output>;
+    type Feature = IdReport<C::Feature>;
+
+    fn input(&self) -> Self::Input {
+        IdReport::new(self.id, self.class.input())
+    }
+
+    fn output(&self) -> Self::Output {
+        IdReport::new(self.id, self.class.output())
+    }
+
+    fn feature(&self) -> Self::Feature {
+        IdReport::new(self.id, self.class.feature())
+    }
+}
+
+/// Reports which is selected by report ID
+///
+/// Implemented for [`IdReport`] and tuples of them to dispatch output reports of several
+/// types received over one interface.
+pub trait Demux {
+    /// Get maximum length of report including ID
+    fn max_len(&self) -> usize;
+
+    /// Store received report into report with matching ID
+    ///
+    /// Returns ID of updated report.
+    fn demux(&mut self, raw: &[u8]) -> Result<u8>;
+}
+
+impl<R: RawReport> IdReport<R> {
+    /// Store received report when ID matches
+    fn accept(&mut self, raw: &[u8]) -> Option<Result<u8>> {
+        if raw.first() != Some(&self.id) {
+            return None;
+        }
+        let data = self.as_mut();
+        Some(if raw.len() == data.len() {
+            data.copy_from_slice(raw);
+            Ok(raw[0])
+        } else {
+            Err(Error::ShortRead {
+                read: raw.len(),
+                expected: data.len(),
+            })
+        })
+    }
+}
+
+fn unknown_id(raw: &[u8]) -> Error {
+    match raw.first() {
+        Some(id) => Error::invalid_report(format!("Unknown report ID {id}")),
+        None => Error::invalid_report("Report without ID"),
+    }
+}
+
+impl<R: RawReport> Demux for IdReport<R> {
+    fn max_len(&self) -> usize {
+        size_of::<Self>()
+    }
+
+    fn demux(&mut self, raw: &[u8]) -> Result<u8> {
+        self.accept(raw).unwrap_or_else(|| Err(unknown_id(raw)))
+    }
+}
+
+macro_rules! demux_tuple {
+    ($($($type:ident: $index:tt),*;)*) => {
+        $(
+            impl<$($type: RawReport),*> Demux for ($(IdReport<$type>,)*) {
+                fn max_len(&self) -> usize {
+                    0 $(.max(self.$index.max_len()))*
+                }
+
+                fn demux(&mut self, raw: &[u8]) -> Result<u8> {
+                    $(
+                        if let Some(result) = self.$index.accept(raw) {
+                            return result;
+                        }
+                    )*
+                    Err(unknown_id(raw))
+                }
+            }
+        )*
+    };
+}
+
+demux_tuple! {
+    A: 0;
+    A: 0, B: 1;
+    A: 0, B: 1, C: 2;
+    A: 0, B: 1, C: 2, D: 3;
+}
+
+#[cfg(all(test, feature = "keyboard", feature = "mouse"))]
+mod test {
+    use super::*;
+    use crate::{Key, Keyboard, Led, Mouse, MouseInput};
+
+    #[test]
+    fn id_report_layout() {
+        let mut input = Identified::new(Keyboard, 1).input();
+        input.press_key(Key::A);
+        assert_eq!(input.as_ref(), [1, 0, 0, 4, 0, 0, 0, 0, 0]);
+
+        let input = Identified::new(Mouse, 2).input();
+        assert_eq!(input.as_ref().len(), 1 + size_of::<MouseInput>());
+    }
+
+    #[test]
+    fn demux_outputs() {
+        let mut outputs = (
+            Identified::new(Keyboard, 1).output(),
+            IdReport::new(3, [0u8; 2]),
+        );
+        assert_eq!(outputs.max_len(), 3);
+
+        assert_eq!(outputs.demux(&[1, 0x02]).unwrap(), 1);
+        assert_eq!(outputs.0.lit().collect::<Vec<_>>(), [Led::CapsLock]);
+
+        assert_eq!(outputs.demux(&[3, 7, 9]).unwrap(), 3);
+        assert_eq!(*outputs.1, [7, 9]);
+
+        assert!(matches!(
+            outputs.demux(&[3, 7]),
+            Err(Error::ShortRead {
+                read: 2,
+                expected: 3
+            })
+        ));
+        assert!(matches!(
+            outputs.demux(&[2, 0]),
+            Err(Error::InvalidReport(_))
+        ));
+        assert!(matches!(outputs.demux(&[]), Err(Error::InvalidReport(_))));
+    }
+}
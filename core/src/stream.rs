@@ -0,0 +1,110 @@
+//! Stream adapters
+
+#[cfg(feature = "keyboard")]
+pub use keyboard::LedChanges;
+
+#[cfg(feature = "keyboard")]
+mod keyboard {
+    use std::{
+        collections::VecDeque,
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+
+    use futures_core::Stream;
+
+    use crate::{KeyboardOutput, Led, Result, StateChange};
+
+    /// Stream of LED state changes
+    ///
+    /// Diffs consecutive keyboard output reports and yields changed LEDs one by one.
+    /// First report is compared with report where all LEDs is off.
+    pub struct LedChanges<S> {
+        stream: S,
+        last: KeyboardOutput,
+        changes: VecDeque<StateChange<Led>>,
+    }
+
+    impl<S> LedChanges<S> {
+        /// Wrap stream of output reports
+        pub fn new(stream: S) -> Self {
+            Self {
+                stream,
+                last: KeyboardOutput::default(),
+                changes: VecDeque::new(),
+            }
+        }
+
+        /// Get last received output report
+        pub fn last(&self) -> &KeyboardOutput {
+            &self.last
+        }
+
+        /// Unwrap underlying stream
+        pub fn into_inner(self) -> S {
+            self.stream
+        }
+    }
+
+    impl<S: Stream<Item = Result<KeyboardOutput>> + Unpin> Stream for LedChanges<S> {
+        type Item = Result<StateChange<Led>>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = &mut *self;
+            loop {
+                if let Some(change) = this.changes.pop_front() {
+                    return Poll::Ready(Some(Ok(change)));
+                }
+                let output = match ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+                    Some(Ok(output)) => output,
+                    Some(Err(error)) => return Poll::Ready(Some(Err(error))),
+                    None => return Poll::Ready(None),
+                };
+                this.changes.extend(output.diff(&this.last));
+                this.last = output;
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use std::task::Waker;
+
+        struct Outputs(VecDeque<Result<KeyboardOutput>>);
+
+        impl Stream for Outputs {
+            type Item = Result<KeyboardOutput>;
+
+            fn poll_next(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+            ) -> Poll<Option<Self::Item>> {
+                Poll::Ready(self.0.pop_front())
+            }
+        }
+
+        #[test]
+        fn led_changes() {
+            let mut first = KeyboardOutput::default();
+            first.change_led(Led::NumLock, true);
+            let mut second = first;
+            second.change_led(Led::NumLock, false);
+            second.change_led(Led::CapsLock, true);
+
+            let mut changes = LedChanges::new(Outputs(
+                [Ok(first), Ok(first), Ok(second)].into_iter().collect(),
+            ));
+            let mut cx = Context::from_waker(Waker::noop());
+            let mut next = || match Pin::new(&mut changes).poll_next(&mut cx) {
+                Poll::Ready(item) => item.map(|item| item.unwrap()),
+                Poll::Pending => unreachable!(),
+            };
+
+            assert_eq!(next(), Some(StateChange::on(Led::NumLock)));
+            assert_eq!(next(), Some(StateChange::off(Led::NumLock)));
+            assert_eq!(next(), Some(StateChange::on(Led::CapsLock)));
+            assert_eq!(next(), None);
+        }
+    }
+}
@@ -0,0 +1,656 @@
+use bitflags::bitflags;
+use core::mem::size_of;
+use static_assertions::const_assert_eq;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Class, EmptyReport, RawReport, StateChange};
+
+/// Telephony headset HID class
+///
+/// Gadget function should be configured with [`Telephony::DESCRIPTOR`] as report descriptor.
+#[derive(Clone, Copy, Debug)]
+pub struct Telephony;
+
+impl Telephony {
+    /// Report descriptor of headset with hook switch, flash and mute controls
+    pub const DESCRIPTOR: [u8; 43] = [
+        0x05, 0x0b, // Usage Page (Telephony)
+        0x09, 0x05, // Usage (Headset)
+        0xa1, 0x01, // Collection (Application)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x25, 0x01, //   Logical Maximum (1)
+        0x75, 0x01, //   Report Size (1)
+        0x95, 0x03, //   Report Count (3)
+        0x09, 0x20, //   Usage (Hook Switch)
+        0x09, 0x21, //   Usage (Flash)
+        0x09, 0x2f, //   Usage (Phone Mute)
+        0x81, 0x02, //   Input (Data,Var,Abs)
+        0x95, 0x05, //   Report Count (5)
+        0x81, 0x03, //   Input (Const)
+        0x05, 0x08, //   Usage Page (LED)
+        0x95, 0x03, //   Report Count (3)
+        0x09, 0x18, //   Usage (Ring)
+        0x09, 0x09, //   Usage (Mute)
+        0x09, 0x17, //   Usage (Off-Hook)
+        0x91, 0x02, //   Output (Data,Var,Abs)
+        0x95, 0x05, //   Report Count (5)
+        0x91, 0x03, //   Output (Const)
+        0xc0, // End Collection
+    ];
+}
+
+impl Class for Telephony {
+    type Input = TelephonyInput;
+    type Output = TelephonyOutput;
+    type Feature = EmptyReport;
+
+    fn input(&self) -> Self::Input {
+        Self::Input::default()
+    }
+
+    fn output(&self) -> Self::Output {
+        Self::Output::default()
+    }
+
+    fn feature(&self) -> Self::Feature {
+        Self::Feature::default()
+    }
+}
+
+impl AsRef<str> for Telephony {
+    fn as_ref(&self) -> &str {
+        "telephony"
+    }
+}
+
+impl core::fmt::Display for Telephony {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+code_enum! {
+    /// Telephony control code
+    ///
+    /// Unassigned codes is converted to [`PhoneButton::None`].
+    PhoneButton: u8 = None {
+        /// No control
+        None = 0x00 => "none",
+        /// Hook switch
+        ///
+        /// Pressed state means off-hook (call is active).
+        HookSwitch = 0x20 => "hook-switch" | "hook",
+        /// Flash
+        Flash = 0x21 => "flash",
+        /// Phone mute
+        Mute = 0x2f => "mute",
+    }
+}
+
+impl PhoneButton {
+    /// Converts from raw value safely
+    pub fn safe_from(raw: u8) -> Option<Self> {
+        if matches!(raw, 0x20 | 0x21 | 0x2f) {
+            Some(From::from(raw))
+        } else {
+            None
+        }
+    }
+}
+
+bitflags! {
+    /// Telephony controls mask
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct PhoneButtons: u8 {
+        /// Hook switch
+        const HookSwitch = 0x01;
+        /// Flash
+        const Flash = 0x02;
+        /// Phone mute
+        const Mute = 0x04;
+    }
+}
+
+const_assert_eq!(size_of::<PhoneButtons>(), 1);
+
+impl Default for PhoneButtons {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl PhoneButtons {
+    /// Converts from raw value safely
+    pub fn safe_from(raw: u8) -> Option<Self> {
+        Self::from_bits(raw)
+    }
+}
+
+impl From<PhoneButtons> for u8 {
+    fn from(buttons: PhoneButtons) -> Self {
+        buttons.bits()
+    }
+}
+
+impl From<PhoneButton> for PhoneButtons {
+    fn from(code: PhoneButton) -> Self {
+        match code {
+            PhoneButton::None => Self::empty(),
+            PhoneButton::HookSwitch => Self::HookSwitch,
+            PhoneButton::Flash => Self::Flash,
+            PhoneButton::Mute => Self::Mute,
+        }
+    }
+}
+
+impl From<PhoneButtons> for PhoneButton {
+    /// Lowest control of mask is converted
+    fn from(buttons: PhoneButtons) -> Self {
+        match buttons.iter().next() {
+            Some(PhoneButtons::HookSwitch) => Self::HookSwitch,
+            Some(PhoneButtons::Flash) => Self::Flash,
+            Some(PhoneButtons::Mute) => Self::Mute,
+            _ => Self::None,
+        }
+    }
+}
+
+code_enum! {
+    /// Telephony LED code
+    ///
+    /// Unassigned codes is converted to [`PhoneLed::None`].
+    PhoneLed: u8 = None {
+        /// No LED
+        None = 0x00 => "none",
+        /// Mute LED
+        Mute = 0x09 => "mute",
+        /// Off-hook LED
+        OffHook = 0x17 => "off-hook",
+        /// Ring LED
+        Ring = 0x18 => "ring",
+    }
+}
+
+impl PhoneLed {
+    /// Converts from raw value safely
+    pub fn safe_from(raw: u8) -> Option<Self> {
+        if matches!(raw, 0x09 | 0x17 | 0x18) {
+            Some(From::from(raw))
+        } else {
+            None
+        }
+    }
+}
+
+bitflags! {
+    /// Telephony LEDs mask
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct PhoneLeds: u8 {
+        /// Ring LED
+        const Ring = 0x01;
+        /// Mute LED
+        const Mute = 0x02;
+        /// Off-hook LED
+        const OffHook = 0x04;
+    }
+}
+
+const_assert_eq!(size_of::<PhoneLeds>(), 1);
+
+impl Default for PhoneLeds {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl PhoneLeds {
+    /// Converts from raw value safely
+    pub fn safe_from(raw: u8) -> Option<Self> {
+        Self::from_bits(raw)
+    }
+}
+
+impl From<PhoneLeds> for u8 {
+    fn from(leds: PhoneLeds) -> Self {
+        leds.bits()
+    }
+}
+
+impl From<PhoneLed> for PhoneLeds {
+    fn from(code: PhoneLed) -> Self {
+        match code {
+            PhoneLed::None => Self::empty(),
+            PhoneLed::Ring => Self::Ring,
+            PhoneLed::Mute => Self::Mute,
+            PhoneLed::OffHook => Self::OffHook,
+        }
+    }
+}
+
+impl From<PhoneLeds> for PhoneLed {
+    /// Lowest LED of mask is converted
+    fn from(leds: PhoneLeds) -> Self {
+        match leds.iter().next() {
+            Some(PhoneLeds::Ring) => Self::Ring,
+            Some(PhoneLeds::Mute) => Self::Mute,
+            Some(PhoneLeds::OffHook) => Self::OffHook,
+            _ => Self::None,
+        }
+    }
+}
+
+serde_num! {
+    PhoneButtons: u8, "a telephony controls mask";
+    PhoneButton: u8, "a numeric telephony control code";
+    PhoneLeds: u8, "a telephony LEDs mask";
+    PhoneLed: u8, "a numeric telephony LED code";
+}
+
+/// Telephony input report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct TelephonyInput {
+    /// Active controls
+    #[cfg_attr(feature = "serde", serde(rename = "b"))]
+    buttons: PhoneButtons,
+}
+
+const_assert_eq!(size_of::<TelephonyInput>(), 1);
+
+impl TelephonyInput {
+    /// Get controls mask
+    pub fn buttons(&self) -> PhoneButtons {
+        self.buttons
+    }
+
+    /// Get number of active controls
+    pub fn count_pressed(&self) -> usize {
+        self.buttons.bits().count_ones() as _
+    }
+
+    /// Get iterator over active controls
+    pub fn pressed(&self) -> PressedPhoneButtons<'_> {
+        PressedPhoneButtons {
+            buttons: &self.buttons,
+            element: 0,
+        }
+    }
+
+    /// Change controls state
+    pub fn change_buttons(&mut self, mask: PhoneButtons, state: bool) {
+        if state {
+            self.buttons |= mask;
+        } else {
+            self.buttons &= !mask;
+        }
+    }
+
+    /// Activate controls
+    pub fn press_buttons(&mut self, mask: PhoneButtons) {
+        self.change_buttons(mask, true);
+    }
+
+    /// Deactivate controls
+    pub fn release_buttons(&mut self, mask: PhoneButtons) {
+        self.change_buttons(mask, false);
+    }
+
+    /// Change control state
+    pub fn change_button(&mut self, code: PhoneButton, state: bool) {
+        self.change_buttons(code.into(), state);
+    }
+
+    /// Activate control
+    pub fn press_button(&mut self, code: PhoneButton) {
+        self.change_button(code, true);
+    }
+
+    /// Deactivate control
+    pub fn release_button(&mut self, code: PhoneButton) {
+        self.change_button(code, false);
+    }
+
+    /// Check that call is active (hook switch is off-hook)
+    pub fn off_hook(&self) -> bool {
+        self.buttons.contains(PhoneButtons::HookSwitch)
+    }
+
+    /// Get control state changes between two reports
+    ///
+    /// Difference of two reports
+    pub fn diff<'i>(&'i self, other: &'i Self) -> PhoneButtonChanges<'i> {
+        PhoneButtonChanges {
+            new: self,
+            old: other,
+            element: 0,
+        }
+    }
+}
+
+impl<'i> core::ops::Sub<&'i TelephonyInput> for &'i TelephonyInput {
+    type Output = PhoneButtonChanges<'i>;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.diff(other)
+    }
+}
+
+/// Changes between telephony input reports
+pub struct PhoneButtonChanges<'i> {
+    new: &'i TelephonyInput,
+    old: &'i TelephonyInput,
+    element: u8,
+}
+
+impl<'i> Iterator for PhoneButtonChanges<'i> {
+    type Item = StateChange<PhoneButton>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.element < 8 {
+            // find changed controls
+            let mask = PhoneButtons::from_bits_retain(1 << self.element);
+            self.element += 1;
+            if (self.new.buttons ^ self.old.buttons).intersects(mask) {
+                let state = self.new.buttons.contains(mask);
+                return Some(StateChange::new(mask.into(), state));
+            }
+        }
+        None
+    }
+}
+
+impl Extend<StateChange<PhoneButtons>> for TelephonyInput {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = StateChange<PhoneButtons>>,
+    {
+        for StateChange { data, state } in iter {
+            self.change_buttons(data, state);
+        }
+    }
+}
+
+impl Extend<StateChange<PhoneButton>> for TelephonyInput {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = StateChange<PhoneButton>>,
+    {
+        for StateChange { data, state } in iter {
+            self.change_button(data, state);
+        }
+    }
+}
+
+/// An iterator over active telephony controls
+pub struct PressedPhoneButtons<'i> {
+    buttons: &'i PhoneButtons,
+    element: u8,
+}
+
+impl<'i> Iterator for PressedPhoneButtons<'i> {
+    type Item = PhoneButton;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.element < 8 {
+            let mask = PhoneButtons::from_bits_retain(1u8 << self.element);
+            self.element += 1;
+            if self.buttons.contains(mask) {
+                return Some(mask.into());
+            }
+        }
+        None
+    }
+}
+
+/// Telephony output report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(C, packed)]
+pub struct TelephonyOutput {
+    /// Active LEDs
+    #[cfg_attr(feature = "serde", serde(rename = "led"))]
+    leds: PhoneLeds,
+}
+
+const_assert_eq!(size_of::<TelephonyOutput>(), 1);
+
+impl TelephonyOutput {
+    /// Get LEDs mask
+    pub fn leds(&self) -> PhoneLeds {
+        self.leds
+    }
+
+    /// Get number of lit LEDs
+    pub fn count_lit(&self) -> usize {
+        self.leds.bits().count_ones() as _
+    }
+
+    /// Check that LED is lit
+    pub fn is_lit(&self, led: PhoneLed) -> bool {
+        let mask = PhoneLeds::from(led);
+        !mask.is_empty() && self.leds.contains(mask)
+    }
+
+    /// Get iterator over lit LEDs
+    pub fn lit(&self) -> LitPhoneLeds<'_> {
+        LitPhoneLeds {
+            leds: &self.leds,
+            element: 0,
+        }
+    }
+
+    /// Change LEDs state
+    pub fn change_leds(&mut self, leds: PhoneLeds, state: bool) {
+        if state {
+            self.leds |= leds;
+        } else {
+            self.leds &= !leds;
+        }
+    }
+
+    /// Turn LEDs on
+    pub fn on_leds(&mut self, leds: PhoneLeds) {
+        self.change_leds(leds, true);
+    }
+
+    /// Turn LEDs off
+    pub fn off_leds(&mut self, leds: PhoneLeds) {
+        self.change_leds(leds, false);
+    }
+
+    /// Change LED state
+    pub fn change_led(&mut self, led: PhoneLed, state: bool) {
+        self.change_leds(led.into(), state);
+    }
+
+    /// Turn LED on
+    pub fn on_led(&mut self, led: PhoneLed) {
+        self.change_led(led, true);
+    }
+
+    /// Turn LED off
+    pub fn off_led(&mut self, led: PhoneLed) {
+        self.change_led(led, false);
+    }
+
+    /// Get LED state changes between two reports
+    ///
+    /// Difference of two reports
+    pub fn diff<'i>(&'i self, other: &'i Self) -> PhoneLedChanges<'i> {
+        PhoneLedChanges {
+            new: self,
+            old: other,
+            element: 0,
+        }
+    }
+}
+
+impl<'i> core::ops::Sub<&'i TelephonyOutput> for &'i TelephonyOutput {
+    type Output = PhoneLedChanges<'i>;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.diff(other)
+    }
+}
+
+/// Changes between telephony output reports
+pub struct PhoneLedChanges<'i> {
+    new: &'i TelephonyOutput,
+    old: &'i TelephonyOutput,
+    element: u8,
+}
+
+impl<'i> Iterator for PhoneLedChanges<'i> {
+    type Item = StateChange<PhoneLed>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.element < 8 {
+            // find changed leds
+            let mask = PhoneLeds::from_bits_retain(1 << self.element);
+            self.element += 1;
+            if (self.new.leds ^ self.old.leds).intersects(mask) {
+                let state = self.new.leds.contains(mask);
+                return Some(StateChange::new(mask.into(), state));
+            }
+        }
+        None
+    }
+}
+
+impl Extend<StateChange<PhoneLeds>> for TelephonyOutput {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = StateChange<PhoneLeds>>,
+    {
+        for StateChange { data, state } in iter {
+            self.change_leds(data, state);
+        }
+    }
+}
+
+impl Extend<StateChange<PhoneLed>> for TelephonyOutput {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = StateChange<PhoneLed>>,
+    {
+        for StateChange { data, state } in iter {
+            self.change_led(data, state);
+        }
+    }
+}
+
+/// An iterator over lit telephony LEDs
+pub struct LitPhoneLeds<'i> {
+    leds: &'i PhoneLeds,
+    element: u8,
+}
+
+impl<'i> Iterator for LitPhoneLeds<'i> {
+    type Item = PhoneLed;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.element < 8 {
+            let mask = PhoneLeds::from_bits_retain(1u8 << self.element);
+            self.element += 1;
+            if self.leds.contains(mask) {
+                return Some(mask.into());
+            }
+        }
+        None
+    }
+}
+
+raw_ref! {
+    PhoneButtons;
+    PhoneLeds;
+    TelephonyInput;
+    TelephonyOutput;
+}
+
+raw_mut! {
+    PhoneButtons;
+    PhoneLeds;
+    TelephonyInput;
+    TelephonyOutput;
+}
+
+unsafe impl RawReport for PhoneButtons {}
+unsafe impl RawReport for PhoneLeds {}
+unsafe impl RawReport for TelephonyInput {}
+unsafe impl RawReport for TelephonyOutput {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn telephony_input() {
+        let mut old = Telephony.input();
+        old.press_button(PhoneButton::HookSwitch);
+        assert!(old.off_hook());
+        assert_eq!(old.as_ref(), [0x01]);
+
+        let mut new = old;
+        new.release_button(PhoneButton::HookSwitch);
+        new.press_buttons(PhoneButtons::Mute | PhoneButtons::Flash);
+        assert_eq!(new.count_pressed(), 2);
+        assert_eq!(
+            new.pressed().collect::<Vec<_>>(),
+            [PhoneButton::Flash, PhoneButton::Mute]
+        );
+
+        let changes: Vec<_> = (&new - &old).collect();
+        assert_eq!(
+            changes,
+            [
+                StateChange::release(PhoneButton::HookSwitch),
+                StateChange::press(PhoneButton::Flash),
+                StateChange::press(PhoneButton::Mute),
+            ]
+        );
+
+        old.extend(changes);
+        assert_eq!(old, new);
+    }
+
+    #[test]
+    fn telephony_output() {
+        let old = Telephony.output();
+        let mut new = old;
+        new.on_led(PhoneLed::Ring);
+        new.on_leds(PhoneLeds::Mute);
+        assert!(new.is_lit(PhoneLed::Ring));
+        assert!(!new.is_lit(PhoneLed::OffHook));
+        assert!(!new.is_lit(PhoneLed::None));
+        assert_eq!(new.as_ref(), [0x03]);
+        assert_eq!(
+            new.lit().collect::<Vec<_>>(),
+            [PhoneLed::Ring, PhoneLed::Mute]
+        );
+
+        let mut changes = &new - &old;
+        assert_eq!(changes.next(), Some(StateChange::on(PhoneLed::Ring)));
+        assert_eq!(changes.next(), Some(StateChange::on(PhoneLed::Mute)));
+        assert_eq!(changes.next(), None);
+
+        new.off_led(PhoneLed::Ring);
+        assert_eq!(new.leds(), PhoneLeds::Mute);
+    }
+
+    #[test]
+    fn phone_codes() {
+        assert_eq!(PhoneButton::safe_from(0x2f), Some(PhoneButton::Mute));
+        assert_eq!(PhoneButton::safe_from(0x22), None);
+        assert_eq!(PhoneLed::safe_from(0x18), Some(PhoneLed::Ring));
+        assert_eq!(PhoneLed::safe_from(0x01), None);
+        assert_eq!(PhoneLeds::safe_from(0x08), None);
+        assert_eq!(u8::from(PhoneButton::HookSwitch), 0x20);
+        assert_eq!(PhoneButton::from(0x21), PhoneButton::Flash);
+        assert_eq!(PhoneButton::from(0x05), PhoneButton::None);
+        assert_eq!(PhoneLed::from(0x17), PhoneLed::OffHook);
+        assert_eq!(PhoneLed::from(0xff), PhoneLed::None);
+    }
+}
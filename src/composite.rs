@@ -0,0 +1,120 @@
+use std::fs::File;
+
+use hidg_core::{CompositeRead, CompositeState, CompositeWrite};
+
+use crate::{
+    CompositeEvent, CompositeInput, Device, Identified, Keyboard, KeyboardOutput, Mouse, Result,
+    Transport,
+};
+
+type State<T> =
+    CompositeState<Device<Keyboard, T>, Device<Mouse, T>, Device<Identified<Keyboard>, T>>;
+
+/// Keyboard and mouse driven as one device
+///
+/// Each event results in at most one report write, so writes goes in order of events
+/// regardless of whether keyboard and mouse is separate devices or share one interface.
+pub struct Composite<T = File> {
+    state: State<T>,
+}
+
+impl<T: Transport> Composite<T> {
+    /// Combine separate keyboard and mouse devices
+    pub fn new(keyboard: Device<Keyboard, T>, mouse: Device<Mouse, T>) -> Self {
+        Self {
+            state: CompositeState::separate(keyboard, mouse),
+        }
+    }
+
+    /// Use one interface which carries keyboard and mouse reports with given report IDs
+    pub fn shared(transport: T, keyboard_id: u8, mouse_id: u8) -> Self {
+        Self {
+            state: CompositeState::shared(Device::new(transport), keyboard_id, mouse_id),
+        }
+    }
+
+    /// Get current state of keyboard and mouse
+    pub fn input(&self) -> &CompositeInput {
+        self.state.input()
+    }
+
+    /// Apply event and send changed report
+    ///
+    /// Nothing is sent when event does not change report.
+    pub fn event(&mut self, event: impl Into<CompositeEvent>) -> Result<()> {
+        let Some(write) = self.state.apply(&event.into()) else {
+            return Ok(());
+        };
+        match write {
+            CompositeWrite::Keyboard(device, report) => device.input(&report),
+            CompositeWrite::Mouse(device, report) => device.input(&report),
+            CompositeWrite::SharedKeyboard(device, report) => device.input(&report),
+            CompositeWrite::SharedMouse(device, report) => device.send_report(&report),
+        }
+    }
+
+    /// Apply events one by one
+    ///
+    /// Stops on first failed write.
+    pub fn events<E: Into<CompositeEvent>>(
+        &mut self,
+        events: impl IntoIterator<Item = E>,
+    ) -> Result<()> {
+        events.into_iter().try_for_each(|event| self.event(event))
+    }
+
+    /// Receive keyboard output report
+    pub fn output(&mut self, output: &mut KeyboardOutput) -> Result<()> {
+        match self.state.read() {
+            CompositeRead::Keyboard(device) => device.output(output),
+            CompositeRead::Shared(device, mut report) => {
+                device.receive_report(&mut report)?;
+                *output = report.into_inner();
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod test {
+    use super::*;
+    use crate::{Class, Key, Led, MockTransport, MouseInputChange, StateChange, ValueChange};
+
+    #[test]
+    fn composite_device() {
+        let events = [
+            CompositeEvent::from(StateChange::press(Key::A)),
+            MouseInputChange::Pointer(ValueChange::new((1, 2), true)).into(),
+            StateChange::release(Key::A).into(),
+        ];
+
+        let (keyboard_device, keyboard) = crate::mocked();
+        let (mouse_device, mouse) = crate::mocked();
+        let mut device = Composite::new(keyboard_device, mouse_device);
+        device.events(events).unwrap();
+        assert_eq!(
+            keyboard.take_inputs(),
+            [vec![0, 0, 4, 0, 0, 0, 0, 0], vec![0; 8]]
+        );
+        assert_eq!(mouse.take_inputs(), [[0, 1, 0, 2, 0, 0]]);
+
+        let shared = MockTransport::new();
+        let mut device = Composite::shared(shared.clone(), 1, 2);
+        device.events(events).unwrap();
+        assert_eq!(
+            shared.take_inputs(),
+            [
+                vec![1, 0, 0, 4, 0, 0, 0, 0, 0],
+                vec![2, 0, 1, 0, 2, 0, 0],
+                vec![1, 0, 0, 0, 0, 0, 0, 0, 0]
+            ]
+        );
+        assert_eq!(device.input().mouse().pointer(), (0, 0));
+
+        shared.push_output([1, 0x02]);
+        let mut output = Keyboard.output();
+        device.output(&mut output).unwrap();
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::CapsLock]);
+    }
+}
@@ -0,0 +1,317 @@
+//! FIDO authenticator over CTAPHID transport
+//!
+//! [`Authenticator`] receives CTAPHID packets from host, reassembles requests, dispatches them
+//! to [`Handler`] and sends fragmented responses back. While handler processes request
+//! it can report progress with KEEPALIVE messages which also checks for CANCEL from host.
+
+use std::{
+    fs::File,
+    io,
+    os::unix::io::AsRawFd,
+    time::{Duration, Instant},
+};
+
+use crate::{timeout::wait, Device, Result, Transport};
+
+pub use hidg_core::ctaphid::{
+    packet_channel, packet_command, Assembler, Capabilities, ChannelError, Command, CtapHid,
+    Dispatcher, ErrorCode, Handler, KeepaliveStatus, Message, Packet, Packets, Progress,
+    BROADCAST_CID, CONT_DATA_LEN, INIT_DATA_LEN, KEEPALIVE_INTERVAL, MAX_MESSAGE_LEN, PACKET_LEN,
+    PROTOCOL_VERSION, TRANSACTION_TIMEOUT,
+};
+
+/// FIDO authenticator device
+pub struct Authenticator<H, T = File> {
+    device: Device<CtapHid, T>,
+    assembler: Assembler,
+    dispatcher: Dispatcher<H>,
+}
+
+impl<H: Handler, T: Transport + AsRawFd> Authenticator<H, T> {
+    /// Create authenticator using given device and handler
+    pub fn new(device: Device<CtapHid, T>, handler: H) -> Self {
+        Self {
+            device,
+            assembler: Assembler::default(),
+            dispatcher: Dispatcher::new(handler),
+        }
+    }
+
+    /// Get reference to underlying device
+    pub fn device(&self) -> &Device<CtapHid, T> {
+        &self.device
+    }
+
+    /// Get reference to handler
+    pub fn handler(&self) -> &H {
+        self.dispatcher.handler()
+    }
+
+    /// Get mutable reference to handler
+    pub fn handler_mut(&mut self) -> &mut H {
+        self.dispatcher.handler_mut()
+    }
+
+    /// Get underlying device and handler
+    pub fn into_inner(self) -> (Device<CtapHid, T>, H) {
+        (self.device, self.dispatcher.into_handler())
+    }
+
+    /// Set timeout of waiting continuation packets
+    pub fn set_transaction_timeout(&mut self, timeout: Duration) {
+        self.assembler = Assembler::new(timeout);
+    }
+
+    /// Receive and process one packet
+    ///
+    /// Blocks until packet is received or incomplete request is timed out.
+    pub fn process(&mut self) -> Result<()> {
+        if let Some(deadline) = self.assembler.deadline() {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match wait(self.device.as_raw_fd(), libc::POLLIN, Some(timeout)) {
+                Err(error) if error.kind() == io::ErrorKind::TimedOut => {
+                    if let Some(error) = self.assembler.expire(Instant::now()) {
+                        send(&mut self.device, &error.message())?;
+                    }
+                    return Ok(());
+                }
+                result => result?,
+            }
+        }
+
+        let mut packet = [0; PACKET_LEN];
+        self.device.output(&mut packet)?;
+        let now = Instant::now();
+        if let Some(error) = self.assembler.expire(now) {
+            send(&mut self.device, &error.message())?;
+        }
+        let request = match self.assembler.push(&packet, now) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            Err(error) => return send(&mut self.device, &error.message()),
+        };
+
+        let mut session = Session {
+            device: &mut self.device,
+            cid: request.cid(),
+            cancelled: false,
+            error: None,
+        };
+        let response = self.dispatcher.dispatch(&request, &mut session);
+        if let Some(error) = session.error {
+            return Err(error);
+        }
+        match response {
+            Some(response) => send(&mut self.device, &response),
+            None => Ok(()),
+        }
+    }
+
+    /// Process packets until error
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            self.process()?;
+        }
+    }
+}
+
+fn send<T: Transport>(device: &mut Device<CtapHid, T>, message: &Message) -> Result<()> {
+    message
+        .packets()
+        .try_for_each(|packet| device.input(&packet))
+}
+
+/// Progress reporting of request which is processed
+struct Session<'d, T> {
+    device: &'d mut Device<CtapHid, T>,
+    cid: u32,
+    cancelled: bool,
+    error: Option<crate::Error>,
+}
+
+impl<T: Transport + AsRawFd> Session<'_, T> {
+    fn poll(&mut self, status: KeepaliveStatus) -> Result<()> {
+        send(self.device, &Message::keepalive(self.cid, status))?;
+        // receive packets which is already sent by host
+        while wait(self.device.as_raw_fd(), libc::POLLIN, Some(Duration::ZERO)).is_ok() {
+            let mut packet = [0; PACKET_LEN];
+            self.device.output(&mut packet)?;
+            let cid = packet_channel(&packet);
+            match packet_command(&packet) {
+                Some(Command::Cancel) if cid == self.cid => self.cancelled = true,
+                Some(_) if cid != self.cid => {
+                    send(self.device, &Message::error(cid, ErrorCode::ChannelBusy))?
+                }
+                // other packets of current channel and continuation packets is ignored
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Transport + AsRawFd> Progress for Session<'_, T> {
+    fn keepalive(&mut self, status: KeepaliveStatus) -> bool {
+        if self.error.is_none() && !self.cancelled {
+            if let Err(error) = self.poll(status) {
+                self.error = Some(error);
+            }
+        }
+        // failed transfer aborts request as cancellation does
+        self.cancelled || self.error.is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixStream,
+    };
+
+    struct Reverse;
+
+    impl Handler for Reverse {
+        fn version(&self) -> [u8; 3] {
+            [1, 2, 3]
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities::Cbor | Capabilities::Nmsg
+        }
+
+        fn handle(
+            &mut self,
+            _command: Command,
+            request: &[u8],
+            progress: &mut dyn Progress,
+        ) -> core::result::Result<Vec<u8>, ErrorCode> {
+            // wait for user presence until host cancels request
+            if request.is_empty() {
+                while !progress.keepalive(KeepaliveStatus::UpNeeded) {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                return Ok(vec![0x2d]);
+            }
+            Ok(request.iter().rev().copied().collect())
+        }
+    }
+
+    fn transact(host: &mut UnixStream, request: &Message) -> Vec<Packet> {
+        for packet in request.packets() {
+            host.write_all(&packet).unwrap();
+        }
+        receive(host)
+    }
+
+    fn receive(host: &mut UnixStream) -> Vec<Packet> {
+        let mut assembler = Assembler::default();
+        let mut packets = Vec::new();
+        loop {
+            let mut packet = [0; PACKET_LEN];
+            host.read_exact(&mut packet).unwrap();
+            packets.push(packet);
+            if assembler.push(&packet, Instant::now()).unwrap().is_some() {
+                return packets;
+            }
+        }
+    }
+
+    fn message(packets: &[Packet]) -> Message {
+        let mut assembler = Assembler::default();
+        packets
+            .iter()
+            .find_map(|packet| assembler.push(packet, Instant::now()).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn authenticator_socket() {
+        let (stream, mut host) = UnixStream::pair().unwrap();
+        let thread = std::thread::spawn(move || {
+            let mut authenticator = Authenticator::new(Device::new(stream), Reverse);
+            authenticator.set_transaction_timeout(Duration::from_millis(50));
+            // runs until host hangs up
+            authenticator.run().unwrap_err()
+        });
+
+        let nonce = [8, 7, 6, 5, 4, 3, 2, 1];
+        let init = Message::new(BROADCAST_CID, Command::Init, nonce).unwrap();
+        let response = message(&transact(&mut host, &init));
+        assert_eq!(response.data()[..8], nonce);
+        assert_eq!(response.data()[12..], [2, 1, 2, 3, 0x0c]);
+        let cid = u32::from_be_bytes(response.data()[8..12].try_into().unwrap());
+
+        let request = Message::new(cid, Command::Cbor, [1, 2, 3]).unwrap();
+        let response = message(&transact(&mut host, &request));
+        assert_eq!(
+            response,
+            Message::new(cid, Command::Cbor, [3, 2, 1]).unwrap()
+        );
+
+        let request = Message::new(cid, Command::Lock, [1]).unwrap();
+        let response = message(&transact(&mut host, &request));
+        assert_eq!(response, Message::error(cid, ErrorCode::InvalidCmd));
+
+        for packet in Message::new(cid, Command::Cancel, []).unwrap().packets() {
+            host.write_all(&packet).unwrap();
+        }
+
+        let data: Vec<u8> = (0..=255).collect();
+        let request = Message::new(cid, Command::Cbor, data.clone()).unwrap();
+        let packets = transact(&mut host, &request);
+        assert_eq!(packets.len(), 5);
+        let response = message(&packets);
+        assert_eq!(
+            response.data(),
+            data.iter().rev().copied().collect::<Vec<_>>()
+        );
+
+        let request = Message::new(cid, Command::Cbor, []).unwrap();
+        let keepalive = message(&transact(&mut host, &request));
+        assert_eq!(
+            keepalive,
+            Message::keepalive(cid, KeepaliveStatus::UpNeeded)
+        );
+        // other channel is busy while request is processed
+        let other = Message::new(cid + 1, Command::Ping, []).unwrap();
+        for packet in other
+            .packets()
+            .chain(Message::new(cid, Command::Cancel, []).unwrap().packets())
+        {
+            host.write_all(&packet).unwrap();
+        }
+        let mut responses = Vec::new();
+        while responses.last().map(Message::command) != Some(Command::Cbor) {
+            let response = message(&receive(&mut host));
+            if response.command() != Command::Keepalive {
+                responses.push(response);
+            }
+        }
+        assert_eq!(
+            responses,
+            [
+                Message::error(cid + 1, ErrorCode::ChannelBusy),
+                Message::new(cid, Command::Cbor, [0x2d]).unwrap()
+            ]
+        );
+
+        // incomplete request is timed out
+        let request = Message::new(cid, Command::Cbor, vec![0; 100]).unwrap();
+        host.write_all(&request.packets().next().unwrap()).unwrap();
+        let response = message(&receive(&mut host));
+        assert_eq!(response, Message::error(cid, ErrorCode::MsgTimeout));
+
+        let request = Message::new(BROADCAST_CID, Command::Ping, []).unwrap();
+        let response = message(&transact(&mut host, &request));
+        assert_eq!(
+            response,
+            Message::error(BROADCAST_CID, ErrorCode::InvalidChannel)
+        );
+
+        drop(host);
+        let error = thread.join().unwrap();
+        assert!(matches!(error, crate::Error::ShortRead { read: 0, .. }));
+    }
+}
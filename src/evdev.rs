@@ -0,0 +1,652 @@
+//! Linux input event devices (/dev/input/eventX) forwarding
+//!
+//! Physical keyboards and mice attached to the gadget board can be mirrored to the host
+//! using [`Forward`] which translates input events into HID reports and reflects keyboard
+//! LEDs back to the source devices.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    os::unix::io::{AsRawFd, RawFd},
+    path::Path,
+};
+
+use crate::{
+    sys::{check, ioc},
+    Axis, Button, Class, Device, Key, Keyboard, KeyboardInput, KeyboardOutput, Led, Mouse,
+    MouseInput, PanMouse, PanMouseInput, Result,
+};
+
+pub use hidg_core::evdev::{KeyCode, LedCode, RelCode};
+
+/// Synchronization events
+pub const EV_SYN: u16 = 0x00;
+/// Key and button events
+pub const EV_KEY: u16 = 0x01;
+/// Relative axis events
+pub const EV_REL: u16 = 0x02;
+/// LED events
+pub const EV_LED: u16 = 0x11;
+
+/// End of events packet
+pub const SYN_REPORT: u16 = 0;
+/// Events buffer overrun
+pub const SYN_DROPPED: u16 = 3;
+
+/// Number of key and button codes
+const KEY_CNT: usize = 0x300;
+
+const EVIOCGRAB: u32 = ioc(ioc::WRITE, b'E', 0x90, core::mem::size_of::<libc::c_int>());
+const EVIOCGKEY: u32 = ioc(ioc::READ, b'E', 0x18, KEY_CNT / 8);
+const EVIOCGBIT_KEY: u32 = ioc(ioc::READ, b'E', 0x20 + EV_KEY as u8, KEY_CNT / 8);
+
+/// Input event
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct InputEvent(libc::input_event);
+
+impl InputEvent {
+    /// Create new input event
+    pub fn new(kind: u16, code: u16, value: i32) -> Self {
+        // SAFETY: input_event is a plain C struct
+        let mut event: libc::input_event = unsafe { core::mem::zeroed() };
+        event.type_ = kind;
+        event.code = code;
+        event.value = value;
+        Self(event)
+    }
+
+    /// Get event type
+    pub fn kind(&self) -> u16 {
+        self.0.type_
+    }
+
+    /// Get event code
+    pub fn code(&self) -> u16 {
+        self.0.code
+    }
+
+    /// Get event value
+    pub fn value(&self) -> i32 {
+        self.0.value
+    }
+}
+
+impl core::fmt::Debug for InputEvent {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("InputEvent")
+            .field("kind", &self.kind())
+            .field("code", &self.code())
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+impl AsRef<[u8]> for InputEvent {
+    fn as_ref(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(self as *const _ as *const _, core::mem::size_of::<Self>())
+        }
+    }
+}
+
+impl AsMut<[u8]> for InputEvent {
+    fn as_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            core::slice::from_raw_parts_mut(self as *mut _ as *mut _, core::mem::size_of::<Self>())
+        }
+    }
+}
+
+/// Set of key and button codes
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct KeySet([u8; KEY_CNT / 8]);
+
+impl Default for KeySet {
+    fn default() -> Self {
+        Self([0; KEY_CNT / 8])
+    }
+}
+
+impl KeySet {
+    /// Check that set contains code
+    pub fn contains(&self, code: KeyCode) -> bool {
+        let code = code.0 as usize;
+        code < KEY_CNT && self.0[code / 8] & (1 << (code % 8)) != 0
+    }
+
+    /// Add or remove code
+    pub fn set(&mut self, code: KeyCode, state: bool) {
+        let code = code.0 as usize;
+        if code < KEY_CNT {
+            if state {
+                self.0[code / 8] |= 1 << (code % 8);
+            } else {
+                self.0[code / 8] &= !(1 << (code % 8));
+            }
+        }
+    }
+
+    /// Get iterator over codes in set
+    pub fn iter(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        (0..KEY_CNT as u16)
+            .map(KeyCode)
+            .filter(|code| self.contains(*code))
+    }
+}
+
+impl FromIterator<KeyCode> for KeySet {
+    fn from_iter<T: IntoIterator<Item = KeyCode>>(iter: T) -> Self {
+        let mut set = Self::default();
+        for code in iter {
+            set.set(code, true);
+        }
+        set
+    }
+}
+
+impl core::fmt::Debug for KeySet {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|code| code.0))
+            .finish()
+    }
+}
+
+/// Input event device
+pub struct EventDevice {
+    file: File,
+}
+
+impl EventDevice {
+    /// Open input event device by path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        Ok(Self { file })
+    }
+
+    /// Grab device exclusively
+    ///
+    /// Grabbed device does not deliver events to other readers, so local system does not react
+    /// on forwarded input.
+    pub fn grab(&self, grab: bool) -> Result<()> {
+        check(unsafe {
+            libc::ioctl(
+                self.file.as_raw_fd(),
+                EVIOCGRAB as _,
+                if grab { 1 as libc::c_int } else { 0 },
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Get keys and buttons which device supports
+    pub fn supported_keys(&self) -> Result<KeySet> {
+        self.key_bits(EVIOCGBIT_KEY)
+    }
+
+    /// Get keys and buttons which is currently pressed
+    pub fn pressed_keys(&self) -> Result<KeySet> {
+        self.key_bits(EVIOCGKEY)
+    }
+
+    fn key_bits(&self, request: u32) -> Result<KeySet> {
+        let mut keys = KeySet::default();
+        check(unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, keys.0.as_mut_ptr()) })?;
+        Ok(keys)
+    }
+
+    /// Receive input event
+    pub fn read_event(&mut self) -> Result<InputEvent> {
+        let mut event = InputEvent::new(0, 0, 0);
+        let raw = event.as_mut();
+        let len = self.file.read(raw)?;
+
+        hidg_core::check_read(len, raw.len())?;
+
+        Ok(event)
+    }
+
+    /// Send input event
+    pub fn write_event(&mut self, event: &InputEvent) -> Result<()> {
+        let raw = event.as_ref();
+        let len = self.file.write(raw)?;
+
+        hidg_core::check_write(len, raw.len())
+    }
+
+    /// Turn LED on or off
+    pub fn set_led(&mut self, led: Led, state: bool) -> Result<()> {
+        if let Ok(code) = LedCode::try_from(led) {
+            self.write_event(&InputEvent::new(EV_LED, code.into(), state as _))?;
+            self.write_event(&InputEvent::new(EV_SYN, SYN_REPORT, 0))?;
+        }
+        Ok(())
+    }
+
+    /// Try clone device
+    pub fn try_clone(&self) -> Result<Self> {
+        let file = self.file.try_clone()?;
+
+        Ok(Self { file })
+    }
+}
+
+impl AsRawFd for EventDevice {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+/// Mouse class which forwarded events can be sent to
+pub trait ForwardMouse: Class<Input: AsRef<[u8]>> {
+    /// Get input report of class from report with all forwarded axes
+    fn forward_input(input: &PanMouseInput) -> &Self::Input;
+}
+
+impl ForwardMouse for Mouse {
+    fn forward_input(input: &PanMouseInput) -> &MouseInput {
+        input
+    }
+}
+
+impl ForwardMouse for PanMouse {
+    fn forward_input(input: &PanMouseInput) -> &PanMouseInput {
+        input
+    }
+}
+
+/// Input events to HID reports forwarder
+///
+/// Horizontal wheel is forwarded only to mouse with [`PanMouse`] class.
+pub struct Forward<M: ForwardMouse = Mouse> {
+    keyboard: Option<Device<Keyboard>>,
+    mouse: Option<Device<M>>,
+    keyboard_input: KeyboardInput,
+    keyboard_output: KeyboardOutput,
+    mouse_input: PanMouseInput,
+    mouse_idle: Vec<u8>,
+    keyboard_changed: bool,
+    mouse_changed: bool,
+    dropped: bool,
+}
+
+impl<M: ForwardMouse> Forward<M> {
+    /// Create forwarder to keyboard and/or mouse devices
+    pub fn new(keyboard: Option<Device<Keyboard>>, mouse: Option<Device<M>>) -> Self {
+        Self {
+            keyboard,
+            mouse,
+            keyboard_input: Keyboard.input(),
+            keyboard_output: Keyboard.output(),
+            mouse_input: PanMouse.input(),
+            mouse_idle: M::forward_input(&PanMouse.input()).as_ref().to_vec(),
+            keyboard_changed: false,
+            mouse_changed: false,
+            dropped: false,
+        }
+    }
+
+    /// Get current keyboard input report
+    pub fn keyboard_input(&self) -> &KeyboardInput {
+        &self.keyboard_input
+    }
+
+    /// Get current mouse input report
+    pub fn mouse_input(&self) -> &M::Input {
+        M::forward_input(&self.mouse_input)
+    }
+
+    /// Handle input event received from source
+    ///
+    /// Changed reports is sent when end of events packet reached.
+    /// After events buffer overrun the actual keys state is read from source.
+    pub fn event(&mut self, source: &EventDevice, event: &InputEvent) -> Result<()> {
+        self.process(event, || {
+            Ok((source.supported_keys()?, source.pressed_keys()?))
+        })
+    }
+
+    fn process(
+        &mut self,
+        event: &InputEvent,
+        keys: impl FnOnce() -> Result<(KeySet, KeySet)>,
+    ) -> Result<()> {
+        match (event.kind(), event.code()) {
+            (EV_SYN, SYN_DROPPED) => {
+                // events until next report should be discarded
+                self.dropped = true;
+            }
+            (EV_SYN, SYN_REPORT) => {
+                if self.dropped {
+                    self.dropped = false;
+                    // key changes may be lost in discarded events
+                    let (supported, pressed) = keys()?;
+                    self.resync(&supported, &pressed);
+                }
+                self.flush()?;
+            }
+            _ if self.dropped => {}
+            // ignore autorepeat because host repeats keys itself
+            (EV_KEY, code) if event.value() != 2 => {
+                let state = event.value() != 0;
+                if let Ok(button) = Button::try_from(KeyCode(code)) {
+                    self.mouse_input.change_button(button, state);
+                    self.mouse_changed = true;
+                } else if let Ok(key) = Key::try_from(KeyCode(code)) {
+                    self.keyboard_input.change_key(key, state);
+                    self.keyboard_changed = true;
+                }
+            }
+            (EV_REL, code) if RelCode(code) == RelCode::HWHEEL => {
+                let pan = saturate_wheel(self.mouse_input.pan() as i32 + event.value());
+                self.mouse_input.set_pan(pan);
+                self.mouse_changed = true;
+            }
+            (EV_REL, code) => {
+                let (x, y) = self.mouse_input.pointer();
+                match Axis::try_from(RelCode(code)) {
+                    Ok(Axis::X) => self
+                        .mouse_input
+                        .set_pointer((saturate(x as i32 + event.value()), y)),
+                    Ok(Axis::Y) => self
+                        .mouse_input
+                        .set_pointer((x, saturate(y as i32 + event.value()))),
+                    Ok(Axis::Wheel) => {
+                        let wheel = saturate_wheel(self.mouse_input.wheel() as i32 + event.value());
+                        self.mouse_input.set_wheel(wheel);
+                    }
+                    Err(_) => return Ok(()),
+                }
+                self.mouse_changed = true;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Apply actual state of supported keys and buttons
+    fn resync(&mut self, supported: &KeySet, pressed: &KeySet) {
+        for code in supported.iter() {
+            let state = pressed.contains(code);
+            if let Ok(button) = Button::try_from(code) {
+                if self.mouse_input.mods().contains(button.into()) != state {
+                    self.mouse_input.change_button(button, state);
+                    self.mouse_changed = true;
+                }
+            } else if let Ok(key) = Key::try_from(code) {
+                if self.keyboard_input.pressed().any(|pressed| pressed == key) != state {
+                    self.keyboard_input.change_key(key, state);
+                    self.keyboard_changed = true;
+                }
+            }
+        }
+    }
+
+    /// Send changed reports
+    fn flush(&mut self) -> Result<()> {
+        if self.keyboard_changed {
+            self.keyboard_changed = false;
+            if let Some(keyboard) = &mut self.keyboard {
+                keyboard.input(&self.keyboard_input)?;
+            }
+        }
+        if self.mouse_changed {
+            self.mouse_changed = false;
+            let input = M::forward_input(&self.mouse_input);
+            // axes which class does not carry, like pan of plain mouse, leaves report unchanged
+            if input.as_ref() != self.mouse_idle {
+                if let Some(mouse) = &mut self.mouse {
+                    mouse.input(input)?;
+                }
+            }
+            // motion is relative so reset it after sending
+            self.mouse_input.set_pointer((0, 0));
+            self.mouse_input.set_wheel(0);
+            self.mouse_input.set_pan(0);
+            self.mouse_idle = M::forward_input(&self.mouse_input).as_ref().to_vec();
+        }
+        Ok(())
+    }
+
+    /// Receive keyboard output report and reflect changed LEDs to sources
+    pub fn leds(&mut self, sources: &mut [EventDevice]) -> Result<()> {
+        let Some(keyboard) = &mut self.keyboard else {
+            return Ok(());
+        };
+
+        let mut output = Keyboard.output();
+        keyboard.output(&mut output)?;
+
+        for change in output.diff(&self.keyboard_output) {
+            for source in sources.iter_mut() {
+                source.set_led(*change, change.state())?;
+            }
+        }
+
+        self.keyboard_output = output;
+
+        Ok(())
+    }
+
+    /// Forward events from sources and reflect LEDs back until error
+    pub fn run(&mut self, sources: &mut [EventDevice]) -> Result<()> {
+        let mut fds = sources
+            .iter()
+            .map(|source| source.as_raw_fd())
+            .chain(self.keyboard.as_ref().map(|keyboard| keyboard.as_raw_fd()))
+            .map(|fd| libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect::<Vec<_>>();
+
+        loop {
+            let res = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, -1) };
+            if res < 0 {
+                let error = std::io::Error::last_os_error();
+                if error.kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error.into());
+            }
+
+            for index in 0..fds.len() {
+                if fds[index].revents == 0 {
+                    continue;
+                }
+                if index < sources.len() {
+                    let event = sources[index].read_event()?;
+                    self.event(&sources[index], &event)?;
+                } else {
+                    self.leds(sources)?;
+                }
+            }
+        }
+    }
+}
+
+fn saturate(value: i32) -> i16 {
+    value.clamp(i16::MIN as _, i16::MAX as _) as _
+}
+
+fn saturate_wheel(value: i32) -> i8 {
+    value.clamp(i8::MIN as _, i8::MAX as _) as _
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Leds;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hidg-evdev-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn forward_events() {
+        let keyboard_path = temp_path("keyboard");
+        let mouse_path = temp_path("mouse");
+        let events_path = temp_path("events");
+
+        std::fs::write(&keyboard_path, []).unwrap();
+        std::fs::write(&mouse_path, []).unwrap();
+
+        let events = [
+            InputEvent::new(EV_KEY, 29, 1),
+            InputEvent::new(EV_KEY, 30, 1),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+            InputEvent::new(EV_KEY, 30, 2),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+            InputEvent::new(EV_KEY, 30, 0),
+            InputEvent::new(EV_KEY, 0x110, 1),
+            InputEvent::new(EV_REL, 0x00, 5),
+            InputEvent::new(EV_REL, 0x01, -3),
+            InputEvent::new(EV_REL, 0x08, 1),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+            InputEvent::new(EV_SYN, SYN_DROPPED, 0),
+            InputEvent::new(EV_KEY, 29, 0),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+            InputEvent::new(EV_KEY, 0x110, 0),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+        ];
+
+        std::fs::write(
+            &events_path,
+            events
+                .iter()
+                .flat_map(|event| event.as_ref().iter().copied())
+                .collect::<Vec<u8>>(),
+        )
+        .unwrap();
+
+        let mut forward = Forward::<Mouse>::new(
+            Some(Device::open(&keyboard_path).unwrap()),
+            Some(Device::open(&mouse_path).unwrap()),
+        );
+
+        // actual state after overrun: ctrl is released, button is still pressed
+        let supported: KeySet = [KeyCode(29), KeyCode(30), KeyCode(0x110)]
+            .into_iter()
+            .collect();
+        let pressed: KeySet = [KeyCode(0x110)].into_iter().collect();
+
+        let mut source = EventDevice::open(&events_path).unwrap();
+        for _ in 0..events.len() {
+            let event = source.read_event().unwrap();
+            forward
+                .process(&event, || Ok((supported, pressed)))
+                .unwrap();
+        }
+        assert!(source.read_event().is_err());
+
+        let keyboard = std::fs::read(&keyboard_path).unwrap();
+        let mouse = std::fs::read(&mouse_path).unwrap();
+
+        std::fs::remove_file(&keyboard_path).unwrap();
+        std::fs::remove_file(&mouse_path).unwrap();
+        std::fs::remove_file(&events_path).unwrap();
+
+        // ctrl+a pressed, then a released, then dropped ctrl release restored by resync
+        assert_eq!(
+            keyboard,
+            [
+                [0x01, 0, 0x04, 0, 0, 0, 0, 0],
+                [0x01, 0, 0x00, 0, 0, 0, 0, 0],
+                [0x00, 0, 0x00, 0, 0, 0, 0, 0]
+            ]
+            .concat()
+        );
+
+        let mut pressed = Mouse.input();
+        pressed.press_button(Button::Primary);
+        pressed.set_pointer((5, -3));
+        pressed.set_wheel(1);
+        let released = Mouse.input();
+
+        assert_eq!(mouse, [pressed.as_ref(), released.as_ref()].concat());
+        assert_eq!(forward.keyboard_input().pressed().next(), None);
+    }
+
+    #[test]
+    fn forward_pan() {
+        let mouse_path = temp_path("pan-mouse");
+        std::fs::write(&mouse_path, []).unwrap();
+
+        let mut forward = Forward::<PanMouse>::new(None, Some(Device::open(&mouse_path).unwrap()));
+        for event in [
+            InputEvent::new(EV_REL, 0x06, -2),
+            InputEvent::new(EV_REL, 0x06, -1),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+        ] {
+            forward.process(&event, || unreachable!()).unwrap();
+        }
+        assert_eq!(forward.mouse_input().pan(), 0);
+
+        let mouse = std::fs::read(&mouse_path).unwrap();
+        std::fs::remove_file(&mouse_path).unwrap();
+
+        let mut input = PanMouse.input();
+        input.set_pan(-3);
+        assert_eq!(mouse, input.as_ref());
+
+        // plain mouse has no horizontal wheel so nothing is sent
+        std::fs::write(&mouse_path, []).unwrap();
+        let mut forward = Forward::<Mouse>::new(None, Some(Device::open(&mouse_path).unwrap()));
+        for event in [
+            InputEvent::new(EV_REL, 0x06, 1),
+            InputEvent::new(EV_SYN, SYN_REPORT, 0),
+        ] {
+            forward.process(&event, || unreachable!()).unwrap();
+        }
+        let mouse = std::fs::read(&mouse_path).unwrap();
+        std::fs::remove_file(&mouse_path).unwrap();
+        assert!(mouse.is_empty());
+    }
+
+    #[test]
+    fn key_set() {
+        let mut keys = KeySet::default();
+        keys.set(KeyCode(30), true);
+        keys.set(KeyCode(0x2ff), true);
+        keys.set(KeyCode(0x300), true);
+        assert!(keys.contains(KeyCode(30)) && !keys.contains(KeyCode(31)));
+        assert_eq!(
+            keys.iter().collect::<Vec<_>>(),
+            [KeyCode(30), KeyCode(0x2ff)]
+        );
+        keys.set(KeyCode(30), false);
+        assert_eq!(keys.iter().count(), 1);
+    }
+
+    #[test]
+    fn reflect_leds() {
+        let keyboard_path = temp_path("leds-keyboard");
+        let source_path = temp_path("leds-source");
+
+        std::fs::write(&keyboard_path, [Leds::CapsLock.bits()]).unwrap();
+        std::fs::write(&source_path, []).unwrap();
+
+        let mut forward = Forward::<Mouse>::new(Some(Device::open(&keyboard_path).unwrap()), None);
+        let mut sources = [EventDevice::open(&source_path).unwrap()];
+
+        forward.leds(&mut sources).unwrap();
+
+        let events = std::fs::read(&source_path).unwrap();
+
+        std::fs::remove_file(&keyboard_path).unwrap();
+        std::fs::remove_file(&source_path).unwrap();
+
+        assert_eq!(
+            events,
+            [
+                InputEvent::new(EV_LED, 0x01, 1).as_ref(),
+                InputEvent::new(EV_SYN, SYN_REPORT, 0).as_ref(),
+            ]
+            .concat()
+        );
+    }
+}
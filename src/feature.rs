@@ -0,0 +1,126 @@
+use std::{io, mem::size_of, os::unix::io::AsRawFd};
+
+use crate::{
+    sys::{check, ioc},
+    timeout::wait,
+    Class, Device, Error, Result,
+};
+
+/// Maximum length of feature report which can be answered
+pub const MAX_FEATURE_LEN: usize = 64;
+
+/// Answer to GET_REPORT request (`struct usb_hidg_report`)
+#[repr(C)]
+struct GetReport {
+    report_id: u8,
+    userspace_req: u8,
+    length: u16,
+    data: [u8; MAX_FEATURE_LEN],
+    padding: [u8; 4],
+}
+
+const GADGET_HID_READ_GET_REPORT_ID: u32 = ioc(ioc::READ, b'g', 0x41, size_of::<u8>());
+const GADGET_HID_WRITE_GET_REPORT: u32 = ioc(ioc::WRITE, b'g', 0x42, size_of::<GetReport>());
+
+/// Old kernels does not know GET_REPORT ioctls
+fn check_supported(error: Error) -> Error {
+    if error.raw_os_error() == Some(libc::ENOTTY) {
+        Error::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "GET_REPORT is not supported by gadget driver",
+        ))
+    } else {
+        error
+    }
+}
+
+impl<C: Class, T: AsRawFd> Device<C, T> {
+    /// Wait for GET_REPORT request from host
+    ///
+    /// Returns requested report ID (zero when report IDs is not used).
+    /// Fails with [`std::io::ErrorKind::Unsupported`] when gadget driver cannot forward requests
+    /// (Linux before 6.12) and respects read timeout.
+    pub fn feature_request(&mut self) -> Result<u8> {
+        let fd = self.transport.as_raw_fd();
+        wait(fd, libc::POLLPRI, self.read_timeout())?;
+        let mut id = 0u8;
+        check(unsafe { libc::ioctl(fd, GADGET_HID_READ_GET_REPORT_ID as _, &mut id) })
+            .map_err(check_supported)?;
+        Ok(id)
+    }
+
+    /// Answer GET_REPORT request with feature report
+    pub fn answer_feature(&mut self, id: u8, feature: &C::Feature) -> Result<()>
+    where
+        C::Feature: AsRef<[u8]>,
+    {
+        let raw = feature.as_ref();
+        if raw.len() > MAX_FEATURE_LEN {
+            return Err(Error::invalid_report(format!(
+                "feature report is longer than {MAX_FEATURE_LEN} bytes"
+            )));
+        }
+        let mut report = GetReport {
+            report_id: id,
+            userspace_req: 1,
+            length: raw.len() as _,
+            data: [0; MAX_FEATURE_LEN],
+            padding: [0; 4],
+        };
+        report.data[..raw.len()].copy_from_slice(raw);
+        let fd = self.transport.as_raw_fd();
+        check(unsafe { libc::ioctl(fd, GADGET_HID_WRITE_GET_REPORT as _, &report) })
+            .map_err(check_supported)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mouse"))]
+mod test {
+    use super::*;
+    use crate::{HiResMouse, ResolutionMultiplier};
+    use std::{io::ErrorKind, os::unix::net::UnixStream, time::Duration};
+
+    #[test]
+    fn feature_socket() {
+        assert_eq!(size_of::<GetReport>(), 72);
+
+        let (stream, mut host) = UnixStream::pair().unwrap();
+        let mut device = Device::<HiResMouse, _>::new(stream);
+
+        // SET_REPORT data is received by reads
+        std::io::Write::write_all(&mut host, &[0x05]).unwrap();
+        let mut multiplier = HiResMouse.feature();
+        device.feature(&mut multiplier).unwrap();
+        assert_eq!(multiplier, ResolutionMultiplier::new(true, true));
+
+        device
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        let error = device.feature_request().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+
+        let error = device.answer_feature(0, &multiplier).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn hires_mouse_device() {
+        use crate::Scroller;
+
+        let (mut device, mock) = crate::mocked::<HiResMouse>();
+        let mut scroller = Scroller::default();
+
+        // host enables high-resolution vertical wheel
+        mock.push_output([0x01]);
+        let mut multiplier = HiResMouse.feature();
+        device.feature(&mut multiplier).unwrap();
+        scroller.set_multiplier(multiplier);
+
+        let mut input = HiResMouse.input();
+        scroller.scroll(&mut input, (-0.5, 0.5));
+        device.input(&input).unwrap();
+        assert_eq!(mock.inputs(), [[0, 0, 0, 0, 0, 0xc4, 0xff, 0, 0]]);
+    }
+}
@@ -6,26 +6,106 @@ use core::marker::PhantomData;
 use std::{
     fs::{File, OpenOptions},
     io::{Read, Write},
+    os::unix::io::{AsRawFd, RawFd},
 };
 
-use hidg_core::{check_read, check_write, AsDevicePath};
+use hidg_core::{check_error, check_read, check_write, check_write_error, AsDevicePath};
 
-pub use hidg_core::{Class, Result, StateChange, ValueChange};
+use timeout::Timeouts;
+
+pub use hidg_core::{
+    Boot, Class, Coalesce, ConnectionState, ConnectionWatch, ConnectionWatcher, Demux, EmptyReport,
+    Error, IdReport, Identified, Pacer, Protocol, RawReport, Result, StateChange, ValueChange,
+};
 
 #[cfg(feature = "keyboard")]
 pub use hidg_core::{
-    Key, KeyStateChanges, Keyboard, KeyboardInput, KeyboardOutput, Led, LedStateChanges, Leds,
-    Modifiers,
+    ExtendedKeyboard, ExtendedKeyboardOutput, Key, KeyStateChanges, Keyboard, KeyboardInput,
+    KeyboardOutput, Led, LedStateChanges, Leds, Modifiers,
 };
 
 #[cfg(feature = "mouse")]
 pub use hidg_core::{
-    Button, Buttons, Mouse, MouseInput, MouseInputChange, MouseInputChanges, MouseOutput,
+    Axis, BootMouseInput, Button, Buttons, GenericMouseInput, GenericMouseInputChange,
+    GenericMouseInputChanges, HiResMouse, HiResMouseInput, Mouse, MouseInput, MouseInputChange,
+    MouseInputChanges, MouseMotion, MouseOutput, PanMouse, PanMouseInput, ResolutionMultiplier,
+    Scroller, WheelValue,
+};
+
+#[cfg(feature = "power")]
+pub use hidg_core::{PowerDevice, PowerFlags, PowerStatus};
+
+#[cfg(feature = "raw-hid")]
+pub use hidg_core::RawHid;
+
+#[cfg(feature = "telephony")]
+pub use hidg_core::{
+    LitPhoneLeds, PhoneButton, PhoneButtonChanges, PhoneButtons, PhoneLed, PhoneLedChanges,
+    PhoneLeds, PressedPhoneButtons, Telephony, TelephonyInput, TelephonyOutput,
 };
 
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+pub use hidg_core::{CompositeEvent, CompositeInput, CompositeTarget};
+
+#[cfg(feature = "mock")]
+pub use hidg_core::mock::{Fault, MockTransport};
+
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+mod composite;
+
+mod feature;
+mod pace;
+#[cfg(feature = "raw-hid")]
+mod raw_hid;
+mod reconnect;
+mod split;
+mod sys;
+mod timeout;
+
+#[cfg(all(feature = "keyboard", feature = "mouse"))]
+pub use composite::Composite;
+
+pub use feature::MAX_FEATURE_LEN;
+pub use pace::Paced;
+pub use reconnect::Reconnecting;
+pub use split::{InputHalf, OutputHalf, ReuniteError};
+
+#[cfg(feature = "ctaphid")]
+pub mod ctaphid;
+
+#[cfg(feature = "evdev")]
+pub mod evdev;
+
+#[cfg(feature = "uinput")]
+pub mod uinput;
+
+/// Report transport
+///
+/// Each call transfers exactly one report like reads and writes of `/dev/hidgX` do.
+/// Implemented for any [`Read`] + [`Write`] stream.
+pub trait Transport {
+    /// Write input report
+    fn write_report(&mut self, report: &[u8]) -> std::io::Result<usize>;
+
+    /// Read output report
+    fn read_report(&mut self, report: &mut [u8]) -> std::io::Result<usize>;
+}
+
+impl<T: Read + Write> Transport for T {
+    fn write_report(&mut self, report: &[u8]) -> std::io::Result<usize> {
+        self.write(report)
+    }
+
+    fn read_report(&mut self, report: &mut [u8]) -> std::io::Result<usize> {
+        self.read(report)
+    }
+}
+
 /// HID Gadget Device
-pub struct Device<C: Class> {
-    file: File,
+pub struct Device<C: Class, T = File> {
+    transport: T,
+    timeouts: Timeouts,
+    protocol: Protocol,
     _class: PhantomData<C>,
 }
 
@@ -34,12 +114,50 @@ impl<C: Class> Device<C> {
     pub fn open(device: impl AsDevicePath) -> Result<Self> {
         let path = device.as_device_path();
 
-        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(|error| Error::open(path, error))?;
+
+        Ok(Self::new(file))
+    }
+
+    /// Try clone device
+    pub fn try_clone(&self) -> Result<Self> {
+        let file = self.transport.try_clone()?;
 
-        Ok(Self {
-            file,
+        Ok(Self::new(file))
+    }
+}
+
+impl<C: Class, T: Transport> Device<C, T> {
+    /// Create device using specific transport
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            timeouts: Timeouts::default(),
+            protocol: Protocol::default(),
             _class: PhantomData,
-        })
+        }
+    }
+
+    /// Get reference to underlying transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get protocol of input reports
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Set protocol of input reports
+    ///
+    /// Gadget driver handles SET_PROTOCOL request itself and does not expose selected protocol
+    /// to userspace, so it should be configured explicitly. Default is report protocol.
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
     }
 
     /// Send input report
@@ -47,8 +165,31 @@ impl<C: Class> Device<C> {
     where
         C::Input: AsRef<[u8]>,
     {
-        let raw = input.as_ref();
-        let len = self.file.write(raw)?;
+        self.send_report(input)
+    }
+
+    /// Send input report encoded for current protocol
+    pub fn protocol_input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C: Boot,
+        C::Input: AsRef<[u8]>,
+    {
+        match self.protocol {
+            Protocol::Boot => self.send_report(&C::boot_input(input)),
+            Protocol::Report => self.send_report(input),
+        }
+    }
+
+    /// Send input report of any type
+    ///
+    /// Useful when interface carries several report types distinguished by report ID.
+    pub fn send_report(&mut self, report: &impl AsRef<[u8]>) -> Result<()> {
+        self.timeouts.wait_write()?;
+        let raw = report.as_ref();
+        let len = self
+            .transport
+            .write_report(raw)
+            .map_err(check_write_error)?;
 
         check_write(len, raw.len())
     }
@@ -58,21 +199,133 @@ impl<C: Class> Device<C> {
     where
         C::Output: AsMut<[u8]>,
     {
+        self.timeouts.wait_read()?;
         let raw = output.as_mut();
-        let len = self.file.read(raw)?;
+        let len = self.transport.read_report(raw).map_err(check_error)?;
 
         check_read(len, raw.len())?;
 
         Ok(())
     }
 
-    /// Try clone device
-    pub fn try_clone(&self) -> Result<Self> {
-        let file = self.file.try_clone()?;
+    /// Receive output report and dispatch it by report ID
+    ///
+    /// Returns ID of received report.
+    pub fn receive_report(&mut self, outputs: &mut impl Demux) -> Result<u8> {
+        self.timeouts.wait_read()?;
+        let mut raw = vec![0; outputs.max_len()];
+        let len = self.transport.read_report(&mut raw).map_err(check_error)?;
 
-        Ok(Self {
-            file,
-            _class: PhantomData,
-        })
+        outputs.demux(&raw[..len])
+    }
+
+    /// Receive feature report set by host
+    ///
+    /// Gadget driver passes data of SET_REPORT requests to reads when function has no OUT
+    /// endpoint, so feature reports share the channel with output reports.
+    pub fn feature(&mut self, feature: &mut C::Feature) -> Result<()>
+    where
+        C::Feature: AsMut<[u8]>,
+    {
+        self.timeouts.wait_read()?;
+        let raw = feature.as_mut();
+        let len = self.transport.read_report(raw).map_err(check_error)?;
+
+        check_read(len, raw.len())?;
+
+        Ok(())
+    }
+}
+
+impl<C: Class, T: AsRawFd> AsRawFd for Device<C, T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.transport.as_raw_fd()
+    }
+}
+
+/// Create device on mock transport and get transport handle to inspect transferred reports
+#[cfg(all(
+    test,
+    feature = "mock",
+    any(feature = "keyboard", feature = "mouse", feature = "raw-hid")
+))]
+fn mocked<C: Class>() -> (Device<C, MockTransport>, MockTransport) {
+    let mock = MockTransport::new();
+    (Device::new(mock.clone()), mock)
+}
+
+#[cfg(all(test, feature = "mock", feature = "keyboard"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mock_device() {
+        let (mut device, mock) = crate::mocked::<Keyboard>();
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        device.input(&input).unwrap();
+        assert_eq!(mock.inputs(), [input.as_ref().to_vec()]);
+
+        mock.fail_write(Fault::Short(1));
+        assert!(device.input(&input).is_err());
+
+        mock.push_output([0x02]);
+        let mut output = Keyboard.output();
+        device.output(&mut output).unwrap();
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::CapsLock]);
+    }
+
+    #[cfg(feature = "mouse")]
+    #[test]
+    fn boot_protocol_device() {
+        let (mut device, mock) = crate::mocked::<Mouse>();
+
+        let mut input = Mouse.input();
+        input.press_button(Button::Primary);
+        input.set_pointer((-200, 3));
+        input.set_wheel(1);
+        device.protocol_input(&input).unwrap();
+        device.set_protocol(Protocol::Boot);
+        device.protocol_input(&input).unwrap();
+        assert_eq!(
+            mock.take_inputs(),
+            [vec![1, 0x38, 0xff, 3, 0, 1], vec![1, 0x80, 3]]
+        );
+
+        let (mut input_half, output_half) = device.split();
+        assert_eq!(input_half.protocol(), Protocol::Boot);
+        input_half.protocol_input(&input).unwrap();
+        assert_eq!(mock.take_inputs(), [[1, 0x80, 3]]);
+        let device = input_half.reunite(output_half).unwrap();
+        assert_eq!(device.protocol(), Protocol::Boot);
+    }
+
+    #[cfg(feature = "mouse")]
+    #[test]
+    fn report_id_device() {
+        let (mut device, mock) = crate::mocked::<Identified<Keyboard>>();
+        let mouse = Identified::new(Mouse, 2);
+
+        let mut input = Identified::new(Keyboard, 1).input();
+        input.press_key(Key::A);
+        device.input(&input).unwrap();
+        let mut motion = mouse.input();
+        motion.set_pointer((1, 0));
+        device.send_report(&motion).unwrap();
+        assert_eq!(
+            mock.take_inputs(),
+            [vec![1, 0, 0, 4, 0, 0, 0, 0, 0], vec![2, 0, 1, 0, 0, 0, 0]]
+        );
+
+        let mut outputs = (IdReport::new(1, Keyboard.output()), mouse.output());
+        mock.push_output([1, 0x01]);
+        mock.push_output([2]);
+        assert_eq!(device.receive_report(&mut outputs).unwrap(), 1);
+        assert_eq!(outputs.0.lit().collect::<Vec<_>>(), [Led::NumLock]);
+        assert_eq!(device.receive_report(&mut outputs).unwrap(), 2);
+
+        mock.push_output([3, 0]);
+        assert!(device.receive_report(&mut outputs).is_err());
     }
 }
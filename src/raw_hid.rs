@@ -0,0 +1,42 @@
+use crate::{Device, RawHid, Result, Transport};
+
+impl<const IN: usize, const OUT: usize, T: Transport> Device<RawHid<IN, OUT>, T> {
+    /// Send request packet and wait for response packet from host
+    pub fn request(&mut self, request: &[u8; IN]) -> Result<[u8; OUT]> {
+        self.input(request)?;
+        let mut response = [0; OUT];
+        self.output(&mut response)?;
+        Ok(response)
+    }
+
+    /// Wait for request packet from host and send response packet
+    ///
+    /// Response is produced by given handler from received request.
+    pub fn respond(&mut self, handler: impl FnOnce(&[u8; OUT]) -> [u8; IN]) -> Result<()> {
+        let mut request = [0; OUT];
+        self.output(&mut request)?;
+        self.input(&handler(&request))
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn raw_hid_device() {
+        type Hid = RawHid<4, 2>;
+
+        let (mut device, mock) = crate::mocked::<Hid>();
+
+        mock.push_output([0xaa, 0xbb]);
+        let response = device.request(&Hid::packet(&[1, 2]).unwrap()).unwrap();
+        assert_eq!(response, [0xaa, 0xbb]);
+
+        mock.push_output([3, 4]);
+        device
+            .respond(|request| Hid::packet(&request[..]).unwrap())
+            .unwrap();
+        assert_eq!(mock.take_inputs(), [[1, 2, 0, 0], [3, 4, 0, 0]]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    fs::{File, OpenOptions},
+    os::unix::io::AsRawFd,
+    time::Duration,
+};
+
+use hidg_core::{AsDevicePath, Reconnect};
+
+use crate::{Class, ConnectionState, ConnectionWatcher, Device, Error, Result, Transport};
+
+type Opener<T> = Box<dyn FnMut() -> Result<T> + Send>;
+
+type Configure<C, T> = fn(&mut Device<C, T>, &Timeouts) -> Result<()>;
+
+/// Timeouts which is applied to each opened device
+#[derive(Clone, Copy, Default)]
+struct Timeouts {
+    read: Option<Duration>,
+    write: Option<Duration>,
+}
+
+/// Device which survives host disconnects
+///
+/// Keeps current input report and re-sends it when connection is restored.
+/// Device file is re-opened when it becomes unusable, for example after gadget re-enumeration.
+pub struct Reconnecting<C: Class, T = File> {
+    open: Opener<T>,
+    inner: Reconnect<Device<C, T>, C::Input>,
+    timeouts: Timeouts,
+    configure: Option<Configure<C, T>>,
+}
+
+impl<C: Class> Reconnecting<C>
+where
+    C::Input: Default,
+{
+    /// Create reconnecting device by path or name or number
+    ///
+    /// Device file is opened lazily on first transfer.
+    pub fn open(device: impl AsDevicePath) -> Self {
+        let path = device.as_device_path();
+        Self::with_opener(move || {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .map_err(|error| Error::open(&path, error))
+        })
+    }
+}
+
+impl<C: Class, T: Transport> Reconnecting<C, T> {
+    /// Create reconnecting device using function which opens transport
+    pub fn with_opener(open: impl FnMut() -> Result<T> + Send + 'static) -> Self
+    where
+        C::Input: Default,
+    {
+        Self {
+            open: Box::new(open),
+            inner: Reconnect::new(Default::default()),
+            timeouts: Timeouts::default(),
+            configure: None,
+        }
+    }
+
+    /// Get current connection state
+    pub fn state(&self) -> ConnectionState {
+        self.inner.state()
+    }
+
+    /// Subscribe to connection state changes
+    pub fn subscribe(&self) -> ConnectionWatcher {
+        self.inner.subscribe()
+    }
+
+    /// Get current input report
+    pub fn current(&self) -> &C::Input {
+        self.inner.current()
+    }
+
+    /// Send input report
+    ///
+    /// Report is kept as current state even if sending fails.
+    pub fn input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C::Input: AsRef<[u8]> + Clone,
+    {
+        self.inner.set_current(input.clone());
+        self.reconnect()
+    }
+
+    /// Re-send current input report
+    ///
+    /// Call it periodically while disconnected to restore connection.
+    pub fn reconnect(&mut self) -> Result<()>
+    where
+        C::Input: AsRef<[u8]>,
+    {
+        self.ensure_open()?;
+        let (device, input) = self.inner.parts_mut().unwrap();
+        let result = device.input(input);
+        self.inner.update(result)
+    }
+
+    /// Receive output report
+    pub fn output(&mut self, output: &mut C::Output) -> Result<()>
+    where
+        C::Output: AsMut<[u8]>,
+    {
+        self.ensure_open()?;
+        let (device, _) = self.inner.parts_mut().unwrap();
+        let result = device.output(output);
+        self.inner.update(result)
+    }
+
+    fn ensure_open(&mut self) -> Result<()> {
+        if self.inner.is_open() {
+            return Ok(());
+        }
+        let result = (self.open)().map(Device::new).and_then(|mut device| {
+            if let Some(configure) = self.configure {
+                configure(&mut device, &self.timeouts)?;
+            }
+            Ok(device)
+        });
+        self.inner.opened(result)
+    }
+}
+
+impl<C: Class, T: Transport + AsRawFd> Reconnecting<C, T> {
+    /// Set timeout of sending input reports
+    ///
+    /// Timeout is applied to currently opened device and each re-opened one.
+    /// See [`Device::set_write_timeout`] for details.
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.configure(Timeouts {
+            write: timeout,
+            ..self.timeouts
+        })
+    }
+
+    /// Set timeout of receiving output reports
+    ///
+    /// Timeout is applied to currently opened device and each re-opened one.
+    /// See [`Device::set_read_timeout`] for details.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.configure(Timeouts {
+            read: timeout,
+            ..self.timeouts
+        })
+    }
+
+    /// Get timeout of sending input reports
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.timeouts.write
+    }
+
+    /// Get timeout of receiving output reports
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.timeouts.read
+    }
+
+    fn configure(&mut self, timeouts: Timeouts) -> Result<()> {
+        fn apply<C: Class, T: AsRawFd>(
+            device: &mut Device<C, T>,
+            timeouts: &Timeouts,
+        ) -> Result<()> {
+            device.set_read_timeout(timeouts.read)?;
+            device.set_write_timeout(timeouts.write)
+        }
+
+        if let Some((device, _)) = self.inner.parts_mut() {
+            apply(device, &timeouts)?;
+        }
+        self.timeouts = timeouts;
+        self.configure = Some(apply);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mock", feature = "keyboard"))]
+mod test {
+    use super::*;
+    use crate::{Fault, Key, Keyboard, MockTransport};
+
+    #[test]
+    fn reconnecting_device() {
+        let mock = MockTransport::new();
+        let opened = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut device = Reconnecting::<Keyboard, _>::with_opener({
+            let mock = mock.clone();
+            let opened = opened.clone();
+            move || {
+                opened.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(mock.clone())
+            }
+        });
+        let mut watcher = device.subscribe();
+        assert_eq!(device.state(), ConnectionState::Closed);
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        device.input(&input).unwrap();
+        assert_eq!(device.state(), ConnectionState::Connected);
+        assert_eq!(watcher.wait(), ConnectionState::Connected);
+
+        // host unplugged while key is pressed
+        mock.fail_write(Fault::Shutdown);
+        input.press_key(Key::B);
+        let error = device.input(&input).unwrap_err();
+        assert!(matches!(error, Error::NotConnected));
+        assert_eq!(error.kind(), std::io::ErrorKind::NotConnected);
+        assert_eq!(device.state(), ConnectionState::Disconnected);
+
+        mock.fail_write(Fault::Short(1));
+        let error = device.input(&input).unwrap_err();
+        assert!(matches!(error, Error::ShortWrite { written: 1, .. }));
+
+        // host is back so current state is re-sent
+        mock.take_inputs();
+        device.reconnect().unwrap();
+        assert_eq!(device.state(), ConnectionState::Connected);
+        assert_eq!(mock.take_inputs(), [input.as_ref().to_vec()]);
+
+        // device file is gone so it is re-opened
+        mock.fail_write(Fault::Os(libc::ENODEV));
+        assert!(device.reconnect().is_err());
+        assert_eq!(device.state(), ConnectionState::Closed);
+        device.reconnect().unwrap();
+        assert_eq!(device.state(), ConnectionState::Connected);
+        assert_eq!(opened.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn reconnecting_timeout() {
+        use std::{os::unix::net::UnixStream, sync::Mutex};
+
+        let hosts = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let mut device = Reconnecting::<Keyboard, _>::with_opener({
+            let hosts = hosts.clone();
+            move || {
+                let (stream, host) = UnixStream::pair().map_err(Error::Io)?;
+                hosts.lock().unwrap().push(host);
+                Ok(stream)
+            }
+        });
+        let timeout = Duration::from_millis(10);
+        device.set_read_timeout(Some(timeout)).unwrap();
+        assert_eq!(device.read_timeout(), Some(timeout));
+
+        // timeout is applied to lazily opened device which is kept after it
+        let mut output = Keyboard.output();
+        for _ in 0..2 {
+            let error = device.output(&mut output).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+        }
+        assert_eq!(device.state(), ConnectionState::Disconnected);
+        assert_eq!(hosts.lock().unwrap().len(), 1);
+    }
+}
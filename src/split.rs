@@ -0,0 +1,197 @@
+use core::marker::PhantomData;
+use std::{
+    fs::File,
+    io::{Read, Write},
+    sync::Arc,
+};
+
+use hidg_core::{check_error, check_read, check_write, check_write_error};
+
+use crate::{Boot, Class, Device, Protocol, Result, Timeouts};
+
+impl<C: Class, T> Device<C, T>
+where
+    for<'t> &'t T: Read + Write,
+{
+    /// Split device into independent input and output halves
+    ///
+    /// Halves can be moved to different threads and reunited back later.
+    pub fn split(self) -> (InputHalf<C, T>, OutputHalf<C, T>) {
+        let transport = Arc::new(self.transport);
+        (
+            InputHalf {
+                transport: transport.clone(),
+                timeouts: self.timeouts,
+                protocol: self.protocol,
+                _class: PhantomData,
+            },
+            OutputHalf {
+                transport,
+                timeouts: self.timeouts,
+                _class: PhantomData,
+            },
+        )
+    }
+}
+
+/// Input half of device
+pub struct InputHalf<C: Class, T = File> {
+    transport: Arc<T>,
+    timeouts: Timeouts,
+    protocol: Protocol,
+    _class: PhantomData<C>,
+}
+
+/// Output half of device
+pub struct OutputHalf<C: Class, T = File> {
+    transport: Arc<T>,
+    timeouts: Timeouts,
+    _class: PhantomData<C>,
+}
+
+impl<C: Class, T> InputHalf<C, T>
+where
+    for<'t> &'t T: Read + Write,
+{
+    /// Send input report
+    pub fn input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C::Input: AsRef<[u8]>,
+    {
+        self.write_report(input.as_ref())
+    }
+
+    /// Get protocol of input reports
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Set protocol of input reports
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Send input report encoded for current protocol
+    pub fn protocol_input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C: Boot,
+        C::Input: AsRef<[u8]>,
+    {
+        match self.protocol {
+            Protocol::Boot => self.write_report(C::boot_input(input).as_ref()),
+            Protocol::Report => self.write_report(input.as_ref()),
+        }
+    }
+
+    fn write_report(&mut self, raw: &[u8]) -> Result<()> {
+        self.timeouts.wait_write()?;
+        let len = (&*self.transport).write(raw).map_err(check_write_error)?;
+
+        check_write(len, raw.len())
+    }
+
+    /// Reunite with output half
+    pub fn reunite(
+        self,
+        output: OutputHalf<C, T>,
+    ) -> core::result::Result<Device<C, T>, ReuniteError<C, T>> {
+        reunite(self, output)
+    }
+}
+
+impl<C: Class, T> OutputHalf<C, T>
+where
+    for<'t> &'t T: Read + Write,
+{
+    /// Receive output report
+    pub fn output(&mut self, output: &mut C::Output) -> Result<()>
+    where
+        C::Output: AsMut<[u8]>,
+    {
+        self.timeouts.wait_read()?;
+        let raw = output.as_mut();
+        let len = (&*self.transport).read(raw).map_err(check_error)?;
+
+        check_read(len, raw.len())?;
+
+        Ok(())
+    }
+
+    /// Reunite with input half
+    pub fn reunite(
+        self,
+        input: InputHalf<C, T>,
+    ) -> core::result::Result<Device<C, T>, ReuniteError<C, T>> {
+        reunite(input, self)
+    }
+}
+
+fn reunite<C: Class, T>(
+    input: InputHalf<C, T>,
+    output: OutputHalf<C, T>,
+) -> core::result::Result<Device<C, T>, ReuniteError<C, T>> {
+    if !Arc::ptr_eq(&input.transport, &output.transport) {
+        return Err(ReuniteError(input, output));
+    }
+    let (timeouts, protocol) = (input.timeouts, input.protocol);
+    drop(output);
+    let Ok(transport) = Arc::try_unwrap(input.transport) else {
+        unreachable!("Only two halves share transport");
+    };
+    Ok(Device {
+        transport,
+        timeouts,
+        protocol,
+        _class: PhantomData,
+    })
+}
+
+/// Error when trying to reunite halves of different devices
+pub struct ReuniteError<C: Class, T = File>(pub InputHalf<C, T>, pub OutputHalf<C, T>);
+
+impl<C: Class, T> core::fmt::Debug for ReuniteError<C, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("ReuniteError")
+    }
+}
+
+impl<C: Class, T> core::fmt::Display for ReuniteError<C, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("Tried to reunite halves which is not from the same device")
+    }
+}
+
+impl<C: Class, T> std::error::Error for ReuniteError<C, T> {}
+
+#[cfg(all(test, feature = "mock", feature = "keyboard"))]
+mod test {
+    use super::*;
+    use crate::{Key, Keyboard, Led};
+
+    #[test]
+    fn split_device() {
+        let (device, mock) = crate::mocked::<Keyboard>();
+        let (mut input_half, mut output_half) = device.split();
+
+        let writer = std::thread::spawn(move || {
+            let mut input = Keyboard.input();
+            input.press_key(Key::A);
+            input_half.input(&input).unwrap();
+            input_half
+        });
+
+        mock.push_output([0x01]);
+        let mut output = Keyboard.output();
+        output_half.output(&mut output).unwrap();
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::NumLock]);
+
+        let input_half = writer.join().unwrap();
+        assert_eq!(mock.inputs().len(), 1);
+
+        let (_, other_output) = crate::mocked::<Keyboard>().0.split();
+        let Err(ReuniteError(input_half, _)) = input_half.reunite(other_output) else {
+            panic!("Halves of different devices reunited");
+        };
+        assert!(input_half.reunite(output_half).is_ok());
+    }
+}
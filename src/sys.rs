@@ -0,0 +1,49 @@
+//! Linux system calls helpers
+
+use crate::Result;
+
+#[cfg(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64",
+))]
+#[allow(dead_code)]
+pub(crate) mod ioc {
+    pub const NONE: u32 = 1;
+    pub const READ: u32 = 2;
+    pub const WRITE: u32 = 4;
+    pub const SIZE_BITS: u32 = 13;
+}
+
+#[cfg(not(any(
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "sparc",
+    target_arch = "sparc64",
+)))]
+#[allow(dead_code)]
+pub(crate) mod ioc {
+    pub const NONE: u32 = 0;
+    pub const READ: u32 = 2;
+    pub const WRITE: u32 = 1;
+    pub const SIZE_BITS: u32 = 14;
+}
+
+/// Make ioctl request code
+pub(crate) const fn ioc(dir: u32, kind: u8, nr: u8, size: usize) -> u32 {
+    (dir << (8 + 8 + ioc::SIZE_BITS)) | ((size as u32) << 16) | ((kind as u32) << 8) | nr as u32
+}
+
+/// Check result of libc call
+pub(crate) fn check(res: libc::c_int) -> Result<libc::c_int> {
+    if res < 0 {
+        Err(std::io::Error::last_os_error().into())
+    } else {
+        Ok(res)
+    }
+}
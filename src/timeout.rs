@@ -0,0 +1,197 @@
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+    time::{Duration, Instant},
+};
+
+use crate::{Class, Device, Error, Result};
+
+/// Timeouts of report transfers
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Timeouts {
+    fd: RawFd,
+    read: Option<Duration>,
+    write: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            fd: -1,
+            read: None,
+            write: None,
+        }
+    }
+}
+
+impl Timeouts {
+    /// Wait until output report can be read
+    pub fn wait_read(&self) -> Result<()> {
+        if self.read.is_none() {
+            return Ok(());
+        }
+        wait(self.fd, libc::POLLIN, self.read)
+    }
+
+    /// Wait until input report can be written
+    pub fn wait_write(&self) -> Result<()> {
+        if self.write.is_none() {
+            return Ok(());
+        }
+        wait(self.fd, libc::POLLOUT, self.write)
+    }
+}
+
+/// Wait for events on file descriptor using poll(2)
+///
+/// `None` timeout means waiting indefinitely.
+pub(crate) fn wait(fd: RawFd, events: libc::c_short, timeout: Option<Duration>) -> Result<()> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut pollfd = libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    };
+    loop {
+        let millis = match deadline {
+            // round up to not return before deadline
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .min(libc::c_int::MAX as _) as libc::c_int,
+            None => -1,
+        };
+        let res = unsafe { libc::poll(&mut pollfd, 1, millis) };
+        if res < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(error.into());
+        }
+        if res == 0 {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        // errors and hangups is reported by following read or write
+        return Ok(());
+    }
+}
+
+fn check_timeout(timeout: Option<Duration>) -> Result<()> {
+    if timeout == Some(Duration::ZERO) {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot set a 0 duration timeout",
+        )));
+    }
+    Ok(())
+}
+
+impl<C: Class, T: AsRawFd> Device<C, T> {
+    /// Set timeout of sending input reports
+    ///
+    /// When host does not poll device in time [`Device::input`] fails with
+    /// [`std::io::ErrorKind::TimedOut`]. `None` means blocking indefinitely.
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        check_timeout(timeout)?;
+        self.timeouts.fd = self.transport.as_raw_fd();
+        self.timeouts.write = timeout;
+        Ok(())
+    }
+
+    /// Set timeout of receiving output reports
+    ///
+    /// When there is no output report in time [`Device::output`] fails with
+    /// [`std::io::ErrorKind::TimedOut`]. `None` means blocking indefinitely.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        check_timeout(timeout)?;
+        self.timeouts.fd = self.transport.as_raw_fd();
+        self.timeouts.read = timeout;
+        Ok(())
+    }
+
+    /// Get timeout of sending input reports
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.timeouts.write
+    }
+
+    /// Get timeout of receiving output reports
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.timeouts.read
+    }
+
+    /// Switch non-blocking mode
+    ///
+    /// In non-blocking mode transfers which cannot be done immediately fails with
+    /// [`std::io::ErrorKind::WouldBlock`].
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        let fd = self.transport.as_raw_fd();
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let flags = if nonblocking {
+            flags | libc::O_NONBLOCK
+        } else {
+            flags & !libc::O_NONBLOCK
+        };
+        if unsafe { libc::fcntl(fd, libc::F_SETFL, flags) } < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "keyboard"))]
+mod test {
+    use super::*;
+    use crate::Keyboard;
+    use std::{io::ErrorKind, os::unix::net::UnixStream};
+
+    #[test]
+    fn read_timeout() {
+        let (stream, mut host) = UnixStream::pair().unwrap();
+        let mut device = Device::<Keyboard, _>::new(stream);
+
+        device
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        assert_eq!(device.read_timeout(), Some(Duration::from_millis(10)));
+
+        let mut output = Keyboard.output();
+        let start = Instant::now();
+        let error = device.output(&mut output).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        std::io::Write::write_all(&mut host, &[0x01]).unwrap();
+        device.output(&mut output).unwrap();
+
+        assert!(device.set_read_timeout(Some(Duration::ZERO)).is_err());
+    }
+
+    #[test]
+    fn write_timeout() {
+        let (stream, _host) = UnixStream::pair().unwrap();
+        let mut device = Device::<Keyboard, _>::new(stream);
+        let input = Keyboard.input();
+
+        // fill socket buffer until host stops receiving
+        device.set_nonblocking(true).unwrap();
+        let error = loop {
+            if let Err(error) = device.input(&input) {
+                break error;
+            }
+        };
+        assert_eq!(error.kind(), ErrorKind::WouldBlock);
+        assert!(matches!(error, Error::Busy));
+
+        device.set_nonblocking(false).unwrap();
+        device
+            .set_write_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        let error = device.input(&input).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,409 @@
+//! Virtual loopback devices using Linux uinput
+//!
+//! [`Loopback`] has the same interface as [`Device`](crate::Device) but instead of sending
+//! reports to the USB host it injects them as input events into virtual keyboard or mouse
+//! on the local machine. LED changes of virtual keyboard is received as output reports.
+//! This allows to test gadget code without USB device controller.
+
+use core::marker::PhantomData;
+use std::{
+    ffi::CStr,
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    evdev::{
+        EventDevice, InputEvent, KeyCode, LedCode, RelCode, EV_KEY, EV_LED, EV_REL, EV_SYN,
+        SYN_REPORT,
+    },
+    sys::{check, ioc, ioc::NONE, ioc::READ, ioc::WRITE},
+    Axis, Button, Class, Key, Keyboard, KeyboardInput, KeyboardOutput, Led, Mouse, MouseInput,
+    MouseInputChange, MouseOutput, PanMouse, PanMouseInput, Result,
+};
+
+const INT_SIZE: usize = core::mem::size_of::<libc::c_int>();
+
+const UI_DEV_CREATE: u32 = ioc(NONE, b'U', 1, 0);
+const UI_DEV_SETUP: u32 = ioc(WRITE, b'U', 3, core::mem::size_of::<libc::uinput_setup>());
+const UI_SET_EVBIT: u32 = ioc(WRITE, b'U', 100, INT_SIZE);
+const UI_SET_KEYBIT: u32 = ioc(WRITE, b'U', 101, INT_SIZE);
+const UI_SET_RELBIT: u32 = ioc(WRITE, b'U', 102, INT_SIZE);
+const UI_SET_LEDBIT: u32 = ioc(WRITE, b'U', 105, INT_SIZE);
+const fn ui_get_sysname(len: usize) -> u32 {
+    ioc(READ, b'U', 44, len)
+}
+
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// Largest key code which may have mapping
+const KEY_MAX: u16 = 0x2ff;
+
+/// Largest LED code which may have mapping
+const LED_MAX: u16 = 0x0f;
+
+/// Device class which can be emulated using uinput
+pub trait Virtual: Class {
+    /// Get event types and codes which is supported by virtual device
+    fn capabilities() -> Vec<(u16, u16)>;
+
+    /// Get events which turns old input report into new one
+    fn events(old: &Self::Input, new: &Self::Input, events: &mut Vec<InputEvent>);
+
+    /// Virtual device has output report
+    ///
+    /// When it is `false` [`Loopback::output`] fails with unsupported error.
+    const OUTPUT: bool = true;
+
+    /// Apply event from virtual device to output report
+    ///
+    /// Returns `true` when report is changed.
+    fn apply(output: &mut Self::Output, event: &InputEvent) -> bool;
+}
+
+impl Virtual for Keyboard {
+    fn capabilities() -> Vec<(u16, u16)> {
+        (0..=KEY_MAX)
+            .filter(|code| Key::try_from(KeyCode(*code)).is_ok())
+            .map(|code| (EV_KEY, code))
+            .chain(
+                (0..=LED_MAX)
+                    .filter(|code| Led::try_from(LedCode(*code)).is_ok())
+                    .map(|code| (EV_LED, code)),
+            )
+            .collect()
+    }
+
+    fn events(old: &KeyboardInput, new: &KeyboardInput, events: &mut Vec<InputEvent>) {
+        events.extend(new.diff(old).filter_map(|change| {
+            KeyCode::try_from(*change)
+                .ok()
+                .map(|code| InputEvent::new(EV_KEY, code.into(), change.state() as _))
+        }));
+    }
+
+    fn apply(output: &mut KeyboardOutput, event: &InputEvent) -> bool {
+        if event.kind() != EV_LED {
+            return false;
+        }
+        let Ok(led) = Led::try_from(LedCode(event.code())) else {
+            return false;
+        };
+        output.change_led(led, event.value() != 0);
+        true
+    }
+}
+
+impl Virtual for Mouse {
+    const OUTPUT: bool = false;
+
+    fn capabilities() -> Vec<(u16, u16)> {
+        (0x01..=0x08)
+            .filter_map(Button::safe_from)
+            .filter_map(|button| KeyCode::try_from(button).ok())
+            .map(|code| (EV_KEY, code.into()))
+            .chain(
+                [Axis::X, Axis::Y, Axis::Wheel]
+                    .into_iter()
+                    .filter_map(|axis| RelCode::try_from(axis).ok())
+                    .map(|code| (EV_REL, code.into())),
+            )
+            .collect()
+    }
+
+    fn events(old: &MouseInput, new: &MouseInput, events: &mut Vec<InputEvent>) {
+        // buttons is absolute but motion is relative so pointer and wheel is sent as is
+        for change in new.diff(old, false, false) {
+            if let MouseInputChange::Button(change) = change {
+                if let Ok(code) = KeyCode::try_from(*change) {
+                    events.push(InputEvent::new(EV_KEY, code.into(), change.state() as _));
+                }
+            }
+        }
+
+        let (x, y) = new.pointer();
+        for (axis, value) in [(Axis::X, x), (Axis::Y, y), (Axis::Wheel, new.wheel() as _)] {
+            if value != 0 {
+                if let Ok(code) = RelCode::try_from(axis) {
+                    events.push(InputEvent::new(EV_REL, code.into(), value as _));
+                }
+            }
+        }
+    }
+
+    fn apply(_output: &mut MouseOutput, _event: &InputEvent) -> bool {
+        false
+    }
+}
+
+impl Virtual for PanMouse {
+    const OUTPUT: bool = false;
+
+    fn capabilities() -> Vec<(u16, u16)> {
+        let mut capabilities = Mouse::capabilities();
+        capabilities.push((EV_REL, RelCode::HWHEEL.into()));
+        capabilities
+    }
+
+    fn events(old: &PanMouseInput, new: &PanMouseInput, events: &mut Vec<InputEvent>) {
+        Mouse::events(old, new, events);
+
+        if new.pan() != 0 {
+            events.push(InputEvent::new(
+                EV_REL,
+                RelCode::HWHEEL.into(),
+                new.pan() as _,
+            ));
+        }
+    }
+
+    fn apply(_output: &mut MouseOutput, _event: &InputEvent) -> bool {
+        false
+    }
+}
+
+/// Virtual loopback device
+pub struct Loopback<C: Virtual> {
+    uinput: EventDevice,
+    input: C::Input,
+    output: C::Output,
+    events: Vec<InputEvent>,
+    _class: PhantomData<C>,
+}
+
+impl<C: Virtual> Loopback<C>
+where
+    C::Input: Default + Copy,
+    C::Output: Default + Copy,
+{
+    /// Create virtual device with name
+    pub fn create(name: &str) -> Result<Self> {
+        Self::create_with_path("/dev/uinput", name)
+    }
+
+    /// Create virtual device with name using specific uinput device path
+    pub fn create_with_path(uinput: impl AsRef<Path>, name: &str) -> Result<Self> {
+        let uinput = EventDevice::open(uinput)?;
+        let fd = uinput.as_raw_fd();
+
+        let capabilities = C::capabilities();
+
+        let mut kinds = capabilities
+            .iter()
+            .map(|(kind, _)| *kind)
+            .collect::<Vec<_>>();
+        kinds.sort();
+        kinds.dedup();
+
+        for kind in kinds {
+            check(unsafe { libc::ioctl(fd, UI_SET_EVBIT as _, kind as libc::c_int) })?;
+        }
+
+        for (kind, code) in capabilities {
+            let request = match kind {
+                EV_KEY => UI_SET_KEYBIT,
+                EV_REL => UI_SET_RELBIT,
+                EV_LED => UI_SET_LEDBIT,
+                _ => continue,
+            };
+            check(unsafe { libc::ioctl(fd, request as _, code as libc::c_int) })?;
+        }
+
+        // SAFETY: uinput_setup is a plain C struct
+        let mut setup: libc::uinput_setup = unsafe { core::mem::zeroed() };
+        setup.id.bustype = BUS_VIRTUAL;
+        for (dst, src) in setup
+            .name
+            .iter_mut()
+            .zip(name.bytes().take(libc::UINPUT_MAX_NAME_SIZE - 1))
+        {
+            *dst = src as _;
+        }
+
+        check(unsafe { libc::ioctl(fd, UI_DEV_SETUP as _, &setup) })?;
+        check(unsafe { libc::ioctl(fd, UI_DEV_CREATE as _) })?;
+
+        Ok(Self {
+            uinput,
+            input: Default::default(),
+            output: Default::default(),
+            events: Vec::new(),
+            _class: PhantomData,
+        })
+    }
+
+    /// Get path to input event device of virtual device
+    ///
+    /// Events injected by [`Loopback::input`] can be observed by reading this device.
+    pub fn event_path(&self) -> Result<PathBuf> {
+        let mut name = [0u8; 64];
+        check(unsafe {
+            libc::ioctl(
+                self.uinput.as_raw_fd(),
+                ui_get_sysname(name.len()) as _,
+                name.as_mut_ptr(),
+            )
+        })?;
+        let name = CStr::from_bytes_until_nul(&name)
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData))?
+            .to_string_lossy();
+
+        let dir = Path::new("/sys/devices/virtual/input").join(&*name);
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with("event") {
+                return Ok(Path::new("/dev/input").join(name));
+            }
+        }
+
+        Err(std::io::ErrorKind::NotFound.into())
+    }
+
+    /// Send input report
+    pub fn input(&mut self, input: &C::Input) -> Result<()> {
+        self.events.clear();
+        C::events(&self.input, input, &mut self.events);
+        self.events.push(InputEvent::new(EV_SYN, SYN_REPORT, 0));
+
+        for event in &self.events {
+            self.uinput.write_event(event)?;
+        }
+
+        self.input = *input;
+
+        Ok(())
+    }
+
+    /// Receive output report
+    ///
+    /// Waits until output report changed.
+    pub fn output(&mut self, output: &mut C::Output) -> Result<()> {
+        if !C::OUTPUT {
+            return Err(std::io::ErrorKind::Unsupported.into());
+        }
+        loop {
+            let event = self.uinput.read_event()?;
+            if C::apply(&mut self.output, &event) {
+                *output = self.output;
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keyboard_events() {
+        let old = Keyboard.input();
+        let mut new = old;
+        new.press_key(Key::LeftShift);
+        new.press_key(Key::A);
+
+        let mut events = Vec::new();
+        Keyboard::events(&old, &new, &mut events);
+
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.kind(), event.code(), event.value()))
+                .collect::<Vec<_>>(),
+            [(EV_KEY, 42, 1), (EV_KEY, 30, 1)]
+        );
+
+        let mut output = Keyboard.output();
+        assert!(Keyboard::apply(
+            &mut output,
+            &InputEvent::new(EV_LED, 0x01, 1)
+        ));
+        assert!(!Keyboard::apply(
+            &mut output,
+            &InputEvent::new(EV_SYN, 0, 0)
+        ));
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::CapsLock]);
+
+        let leds = Keyboard::capabilities()
+            .into_iter()
+            .filter(|(kind, _)| *kind == EV_LED)
+            .map(|(_, code)| code)
+            .collect::<Vec<_>>();
+        assert_eq!(leds, (0..=10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn mouse_events() {
+        let mut old = Mouse.input();
+        old.press_button(Button::Primary);
+        old.set_pointer((10, 10));
+
+        let mut new = Mouse.input();
+        new.press_button(Button::Secondary);
+        new.set_pointer((0, -5));
+        new.set_wheel(1);
+
+        let mut events = Vec::new();
+        Mouse::events(&old, &new, &mut events);
+
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.kind(), event.code(), event.value()))
+                .collect::<Vec<_>>(),
+            [
+                (EV_KEY, 0x110, 0),
+                (EV_KEY, 0x111, 1),
+                (EV_REL, 0x01, -5),
+                (EV_REL, 0x08, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn pan_mouse_events() {
+        let old = PanMouse.input();
+        let mut new = old;
+        new.press_button(Button::Back);
+        new.set_pan(-2);
+
+        let mut events = Vec::new();
+        PanMouse::events(&old, &new, &mut events);
+
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.kind(), event.code(), event.value()))
+                .collect::<Vec<_>>(),
+            [(EV_KEY, 0x113, 1), (EV_REL, 0x06, -2)]
+        );
+
+        let capabilities = PanMouse::capabilities();
+        for code in 0x110..=0x117 {
+            assert!(capabilities.contains(&(EV_KEY, code)));
+        }
+        assert!(capabilities.contains(&(EV_REL, 0x06)));
+    }
+
+    #[test]
+    #[ignore = "requires access to /dev/uinput"]
+    fn keyboard_loopback() {
+        let mut device = Loopback::<Keyboard>::create("hidg loopback test").unwrap();
+
+        let mut events = EventDevice::open(device.event_path().unwrap()).unwrap();
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        device.input(&input).unwrap();
+
+        let event = events.read_event().unwrap();
+        assert_eq!((event.kind(), event.code(), event.value()), (EV_KEY, 30, 1));
+
+        events.set_led(Led::NumLock, true).unwrap();
+
+        let mut output = Keyboard.output();
+        device.output(&mut output).unwrap();
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::NumLock]);
+    }
+}
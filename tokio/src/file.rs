@@ -0,0 +1,84 @@
+use std::{
+    io::{Read, Result, Write},
+    os::unix::io::{AsRawFd, RawFd},
+    path::Path,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use tokio::io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::Error;
+
+/// Non-blocking device file
+///
+/// Unlike [`tokio::fs::File`] reads and writes is done directly in the caller task so each
+/// call transfers exactly one report and write errors is not deferred.
+pub struct File {
+    fd: AsyncFd<std::fs::File>,
+}
+
+impl File {
+    /// Open device file for reading and writing
+    pub async fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        Self::open_file(path)
+            .await
+            .map_err(|error| Error::open(path, error))
+    }
+
+    async fn open_file(path: &Path) -> Result<Self> {
+        let file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)
+            .await?
+            .into_std()
+            .await;
+        // SAFETY: the file owns its descriptor and is moved into AsyncFd
+        let fd = unsafe { AsyncFd::register(file) }?;
+        Ok(Self { fd })
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        loop {
+            let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+            if let Ok(result) = guard.try_io(|fd| fd.get_ref().read(buf.initialize_unfilled())) {
+                buf.advance(result?);
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        loop {
+            let mut guard = ready!(self.fd.poll_write_ready(cx))?;
+            if let Ok(result) = guard.try_io(|fd| fd.get_ref().write(buf)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsRawFd for File {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
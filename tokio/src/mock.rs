@@ -0,0 +1,56 @@
+use std::{
+    io::Result,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+pub use hidg_core::mock::Fault;
+
+/// In-memory transport
+///
+/// See [`hidg_core::mock::MockTransport`] for details.
+#[derive(Clone, Default)]
+pub struct MockTransport(hidg_core::mock::MockTransport);
+
+impl MockTransport {
+    /// Create new mock transport
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl core::ops::Deref for MockTransport {
+    type Target = hidg_core::mock::MockTransport;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsyncRead for MockTransport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        let len = core::task::ready!(self.0.poll_read_report(cx, buf.initialize_unfilled()))?;
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for MockTransport {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Poll::Ready(self.0.write_report(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,314 @@
+#[cfg(feature = "stream")]
+use core::task::{ready, Context, Poll};
+use core::{future::poll_fn, marker::PhantomData, pin::Pin};
+
+#[cfg(feature = "stream")]
+use futures_core::Stream;
+#[cfg(feature = "stream")]
+use futures_sink::Sink;
+
+use hidg_core::{check_error, check_read, check_write, check_write_error};
+use tokio::io::{split, AsyncRead, AsyncWrite, ReadBuf, ReadHalf, WriteHalf};
+
+use crate::{Boot, Class, Device, File, Protocol, Result};
+
+impl<C: Class, T: AsyncRead + AsyncWrite + Unpin> Device<C, T> {
+    /// Split device into independent input and output halves
+    ///
+    /// Halves can be moved to different tasks and reunited back later.
+    pub fn split(self) -> (InputHalf<C, T>, OutputHalf<C, T>) {
+        let (reader, writer) = split(self.transport);
+        (
+            InputHalf {
+                writer,
+                protocol: self.protocol,
+                #[cfg(feature = "stream")]
+                pending: None,
+                _class: PhantomData,
+            },
+            OutputHalf {
+                reader,
+                _class: PhantomData,
+            },
+        )
+    }
+}
+
+/// Input half of device
+pub struct InputHalf<C: Class, T = File> {
+    writer: WriteHalf<T>,
+    protocol: Protocol,
+    /// Report which is accepted by sink but not sent yet
+    #[cfg(feature = "stream")]
+    pending: Option<C::Input>,
+    _class: PhantomData<C>,
+}
+
+/// Output half of device
+pub struct OutputHalf<C: Class, T = File> {
+    reader: ReadHalf<T>,
+    _class: PhantomData<C>,
+}
+
+impl<C: Class, T: AsyncWrite + Unpin> InputHalf<C, T> {
+    /// Send input report
+    pub async fn input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C::Input: AsRef<[u8]>,
+    {
+        self.write_report(input.as_ref()).await
+    }
+
+    /// Get protocol of input reports
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Set protocol of input reports
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Send input report encoded for current protocol
+    pub async fn protocol_input(&mut self, input: &C::Input) -> Result<()>
+    where
+        C: Boot,
+        C::Input: AsRef<[u8]>,
+    {
+        match self.protocol {
+            Protocol::Boot => self.write_report(C::boot_input(input).as_ref()).await,
+            Protocol::Report => self.write_report(input.as_ref()).await,
+        }
+    }
+
+    async fn write_report(&mut self, raw: &[u8]) -> Result<()> {
+        let len = poll_fn(|cx| Pin::new(&mut self.writer).poll_write(cx, raw))
+            .await
+            .map_err(check_write_error)?;
+
+        check_write(len, raw.len())
+    }
+
+    /// Reunite with output half
+    pub fn reunite(
+        self,
+        output: OutputHalf<C, T>,
+    ) -> core::result::Result<Device<C, T>, ReuniteError<C, T>> {
+        reunite(self, output)
+    }
+}
+
+impl<C: Class, T: AsyncRead + Unpin> OutputHalf<C, T> {
+    /// Receive output report
+    pub async fn output(&mut self, output: &mut C::Output) -> Result<()>
+    where
+        C::Output: AsMut<[u8]>,
+    {
+        let raw = output.as_mut();
+        let mut buf = ReadBuf::new(raw);
+        poll_fn(|cx| Pin::new(&mut self.reader).poll_read(cx, &mut buf))
+            .await
+            .map_err(check_error)?;
+        let len = buf.filled().len();
+
+        check_read(len, raw.len())?;
+
+        Ok(())
+    }
+
+    /// Reunite with input half
+    pub fn reunite(
+        self,
+        input: InputHalf<C, T>,
+    ) -> core::result::Result<Device<C, T>, ReuniteError<C, T>> {
+        reunite(input, self)
+    }
+}
+
+#[cfg(all(feature = "stream", feature = "keyboard"))]
+impl<T> OutputHalf<crate::Keyboard, T> {
+    /// Turn into stream of LED state changes
+    pub fn led_changes(self) -> crate::LedChanges<Self> {
+        crate::LedChanges::new(self)
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<C: Class, T: AsyncRead + Unpin> Stream for OutputHalf<C, T>
+where
+    C::Output: AsMut<[u8]> + Default,
+    Self: Unpin,
+{
+    type Item = Result<C::Output>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut output = C::Output::default();
+        let raw = output.as_mut();
+        let mut buf = ReadBuf::new(raw);
+        let result = ready!(Pin::new(&mut self.get_mut().reader).poll_read(cx, &mut buf))
+            .map_err(check_error);
+        let len = buf.filled().len();
+        // end of file means that device is gone
+        if result.is_ok() && len == 0 && !raw.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        Poll::Ready(Some(
+            result
+                .and_then(|_| check_read(len, raw.len()))
+                .map(|_| output),
+        ))
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<C: Class, T: AsyncWrite + Unpin> Sink<C::Input> for InputHalf<C, T>
+where
+    C::Input: AsRef<[u8]>,
+    Self: Unpin,
+{
+    type Error = crate::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, input: C::Input) -> Result<()> {
+        self.get_mut().pending = Some(input);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if let Some(input) = &this.pending {
+            let raw = input.as_ref();
+            let len = ready!(Pin::new(&mut this.writer).poll_write(cx, raw))
+                .map_err(check_write_error)?;
+            let expected = raw.len();
+            this.pending = None;
+            check_write(len, expected)?;
+        }
+        Pin::new(&mut this.writer)
+            .poll_flush(cx)
+            .map_err(check_write_error)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+fn reunite<C: Class, T: Unpin>(
+    input: InputHalf<C, T>,
+    output: OutputHalf<C, T>,
+) -> core::result::Result<Device<C, T>, ReuniteError<C, T>> {
+    if !output.reader.is_pair_of(&input.writer) {
+        return Err(ReuniteError(input, output));
+    }
+    Ok(Device {
+        transport: output.reader.unsplit(input.writer),
+        protocol: input.protocol,
+        _class: PhantomData,
+    })
+}
+
+/// Error when trying to reunite halves of different devices
+pub struct ReuniteError<C: Class, T = File>(pub InputHalf<C, T>, pub OutputHalf<C, T>);
+
+impl<C: Class, T> core::fmt::Debug for ReuniteError<C, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("ReuniteError")
+    }
+}
+
+impl<C: Class, T> core::fmt::Display for ReuniteError<C, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("Tried to reunite halves which is not from the same device")
+    }
+}
+
+impl<C: Class, T> std::error::Error for ReuniteError<C, T> {}
+
+#[cfg(all(test, feature = "mock", feature = "keyboard"))]
+mod test {
+    use super::*;
+    use crate::{Key, Keyboard, Led};
+
+    #[tokio::test]
+    async fn split_device() {
+        let (device, mock) = crate::mocked::<Keyboard>();
+        let (mut input_half, mut output_half) = device.split();
+
+        let reader = tokio::spawn(async move {
+            let mut output = Keyboard.output();
+            output_half.output(&mut output).await.unwrap();
+            (output_half, output)
+        });
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        input_half.input(&input).await.unwrap();
+        assert_eq!(mock.inputs().len(), 1);
+
+        mock.push_output([0x01]);
+        let (output_half, output) = reader.await.unwrap();
+        assert_eq!(output.lit().collect::<Vec<_>>(), [Led::NumLock]);
+
+        let (_, other_output) = crate::mocked::<Keyboard>().0.split();
+        let Err(ReuniteError(input_half, _)) = input_half.reunite(other_output) else {
+            panic!("Halves of different devices reunited");
+        };
+        assert!(input_half.reunite(output_half).is_ok());
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn stream_sink() {
+        use futures_core::Stream;
+        use futures_sink::Sink;
+
+        use crate::StateChange;
+
+        let (device, mock) = crate::mocked::<Keyboard>();
+        let (mut input_half, output_half) = device.split();
+
+        let mut input = Keyboard.input();
+        input.press_key(Key::A);
+        poll_fn(|cx| Pin::new(&mut input_half).poll_ready(cx))
+            .await
+            .unwrap();
+        Pin::new(&mut input_half).start_send(input).unwrap();
+        assert!(mock.inputs().is_empty());
+        poll_fn(|cx| Pin::new(&mut input_half).poll_flush(cx))
+            .await
+            .unwrap();
+        assert_eq!(mock.inputs(), [input.as_ref().to_vec()]);
+
+        mock.push_output([0x01]);
+        mock.push_output([0x03]);
+        mock.push_output([0x02]);
+
+        let mut changes = output_half.led_changes();
+        let mut leds = Vec::new();
+        while leds.len() < 3 {
+            let change = poll_fn(|cx| Pin::new(&mut changes).poll_next(cx)).await;
+            leds.push(change.unwrap().unwrap());
+        }
+        assert_eq!(
+            leds,
+            [
+                StateChange::on(Led::NumLock),
+                StateChange::on(Led::CapsLock),
+                StateChange::off(Led::NumLock)
+            ]
+        );
+
+        // stream ends when device is gone
+        mock.push_output([0x00]);
+        mock.close();
+        let change = poll_fn(|cx| Pin::new(&mut changes).poll_next(cx)).await;
+        assert_eq!(change.unwrap().unwrap(), StateChange::off(Led::CapsLock));
+        let change = poll_fn(|cx| Pin::new(&mut changes).poll_next(cx)).await;
+        assert!(change.is_none());
+    }
+}